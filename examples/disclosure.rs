@@ -1,7 +1,6 @@
 use std::time::Duration;
 
-use irma::{AttributeRequest, DisclosureRequestBuilder, IrmaClient};
-use tokio::time::sleep;
+use irma::{DisclosureRequestBuilder, IrmaClient};
 
 #[tokio::main]
 async fn main() {
@@ -10,9 +9,7 @@ async fn main() {
 
     // Setup our request
     let request = DisclosureRequestBuilder::new()
-        .add_discon(vec![vec![AttributeRequest::Simple(
-            "pbdf.sidn-pbdf.email.email".into(),
-        )]])
+        .add_attribute("pbdf.sidn-pbdf.email.email")
         .build();
 
     // Start the session
@@ -33,16 +30,11 @@ async fn main() {
         .build();
     println!("\n\n{}", qr);
 
-    // Periodically poll if the session was succesfully concluded
-    let result = loop {
-        match client.result(&session.token).await {
-            Ok(result) => break result,
-            Err(irma::Error::SessionNotFinished(_)) => {}
-            Err(v) => panic!("{}", v),
-        }
-
-        sleep(Duration::from_secs(2)).await;
-    };
+    // Wait for the session to be succesfully concluded
+    let result = client
+        .wait_for_result(&session.token, Duration::from_secs(2), None)
+        .await
+        .expect("Session failed");
 
     println!("Session result: {:?}", result);
 }
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use irma::{AttributeRequest, IrmaClient, SignatureRequestBuilder};
+use irma::{IrmaClient, SignatureRequestBuilder};
 use tokio::time::sleep;
 
 #[tokio::main]
@@ -10,9 +10,7 @@ async fn main() {
 
     // Setup our request
     let request = SignatureRequestBuilder::new("Test message".into())
-        .add_discon(vec![vec![AttributeRequest::Simple(
-            "pbdf.sidn-pbdf.email.email".into(),
-        )]])
+        .add_attribute("pbdf.sidn-pbdf.email.email")
         .build();
 
     // Start the session
@@ -0,0 +1,266 @@
+use crate::{
+    BoxFuture, Error, ExtendedIrmaRequest, IrmaClient, IrmaRequest, SessionData, SessionResult,
+    SessionStatus, SessionToken,
+};
+
+/// Interface for starting and following irma sessions.
+///
+/// Application code that depends on this trait instead of directly on [IrmaClient] can substitute
+/// a fake implementation in its tests (for example the one provided in `fake` when the `test-util`
+/// feature is enabled). The trait is object safe, so it can be used as `Arc<dyn IrmaClientApi>`.
+pub trait IrmaClientApi: Send + Sync {
+    /// Start an IRMA session with the given request
+    fn request<'a>(&'a self, request: &'a IrmaRequest)
+        -> BoxFuture<'a, Result<SessionData, Error>>;
+
+    /// Start an IRMA session with the given extended request
+    fn request_extended<'a>(
+        &'a self,
+        request: &'a ExtendedIrmaRequest,
+    ) -> BoxFuture<'a, Result<SessionData, Error>>;
+
+    /// Get the status of a previously started irma session
+    fn status<'a>(&'a self, token: &'a SessionToken)
+        -> BoxFuture<'a, Result<SessionStatus, Error>>;
+
+    /// Get the result for a previously started irma session
+    fn result<'a>(&'a self, token: &'a SessionToken)
+        -> BoxFuture<'a, Result<SessionResult, Error>>;
+
+    /// Cancel a previously started session
+    fn cancel<'a>(&'a self, token: &'a SessionToken) -> BoxFuture<'a, Result<(), Error>>;
+}
+
+impl IrmaClientApi for IrmaClient {
+    fn request<'a>(
+        &'a self,
+        request: &'a IrmaRequest,
+    ) -> BoxFuture<'a, Result<SessionData, Error>> {
+        Box::pin(IrmaClient::request(self, request))
+    }
+
+    fn request_extended<'a>(
+        &'a self,
+        request: &'a ExtendedIrmaRequest,
+    ) -> BoxFuture<'a, Result<SessionData, Error>> {
+        Box::pin(IrmaClient::request(self, request))
+    }
+
+    fn status<'a>(
+        &'a self,
+        token: &'a SessionToken,
+    ) -> BoxFuture<'a, Result<SessionStatus, Error>> {
+        Box::pin(IrmaClient::status(self, token))
+    }
+
+    fn result<'a>(
+        &'a self,
+        token: &'a SessionToken,
+    ) -> BoxFuture<'a, Result<SessionResult, Error>> {
+        Box::pin(IrmaClient::result(self, token))
+    }
+
+    fn cancel<'a>(&'a self, token: &'a SessionToken) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(IrmaClient::cancel(self, token))
+    }
+}
+
+/// In-memory implementation of [IrmaClientApi], for use in tests of code using irma sessions.
+#[cfg(feature = "test-util")]
+pub mod fake {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use super::{BoxFuture, IrmaClientApi};
+    use crate::{
+        irmaclient::check_finished, Error, ExtendedIrmaRequest, IrmaRequest, Qr, SessionData,
+        SessionResult, SessionStatus, SessionToken, SessionType,
+    };
+
+    struct FakeSession {
+        request: IrmaRequest,
+        status: SessionStatus,
+        result: Option<SessionResult>,
+    }
+
+    /// Fake irma client keeping its sessions in memory. Sessions start out as initialized, and
+    /// only change status when instructed through [FakeIrmaClient::set_status] or
+    /// [FakeIrmaClient::set_result], or when cancelled.
+    #[derive(Default)]
+    pub struct FakeIrmaClient {
+        sessions: Mutex<HashMap<String, FakeSession>>,
+    }
+
+    impl FakeIrmaClient {
+        /// Create a fake client without any sessions
+        pub fn new() -> FakeIrmaClient {
+            FakeIrmaClient::default()
+        }
+
+        /// Change the status of a started session
+        pub fn set_status(&self, token: &SessionToken, status: SessionStatus) {
+            if let Some(session) = self.sessions.lock().unwrap().get_mut(&token.0) {
+                session.status = status;
+            }
+        }
+
+        /// Set the result of a started session, also updating its status to that of the result
+        pub fn set_result(&self, token: &SessionToken, result: SessionResult) {
+            if let Some(session) = self.sessions.lock().unwrap().get_mut(&token.0) {
+                session.status = result.status.clone();
+                session.result = Some(result);
+            }
+        }
+
+        /// The request with which the given session was started
+        pub fn session_request(&self, token: &SessionToken) -> Option<IrmaRequest> {
+            self.sessions
+                .lock()
+                .unwrap()
+                .get(&token.0)
+                .map(|session| session.request.clone())
+        }
+
+        fn start(&self, request: &IrmaRequest) -> SessionData {
+            let mut sessions = self.sessions.lock().unwrap();
+            let token = format!("fakesession{}", sessions.len());
+            let sessiontype = match request {
+                IrmaRequest::Disclosure { .. } => SessionType::Disclosing,
+                IrmaRequest::Signature { .. } => SessionType::Signing,
+                IrmaRequest::Issuance { .. } => SessionType::Issuing,
+            };
+            sessions.insert(
+                token.clone(),
+                FakeSession {
+                    request: request.clone(),
+                    status: SessionStatus::Initialized,
+                    result: None,
+                },
+            );
+            SessionData {
+                session_ptr: Qr {
+                    u: format!("https://irma.example.com/irma/session/{}", token),
+                    irmaqr: sessiontype,
+                },
+                token: SessionToken(token),
+            }
+        }
+
+        fn with_session<T>(
+            &self,
+            token: &SessionToken,
+            f: impl FnOnce(&mut FakeSession) -> Result<T, Error>,
+        ) -> Result<T, Error> {
+            match self.sessions.lock().unwrap().get_mut(&token.0) {
+                Some(session) => f(session),
+                None => Err(Error::SessionUnknown(token.clone())),
+            }
+        }
+    }
+
+    impl IrmaClientApi for FakeIrmaClient {
+        fn request<'a>(
+            &'a self,
+            request: &'a IrmaRequest,
+        ) -> BoxFuture<'a, Result<SessionData, Error>> {
+            Box::pin(async move { Ok(self.start(request)) })
+        }
+
+        fn request_extended<'a>(
+            &'a self,
+            request: &'a ExtendedIrmaRequest,
+        ) -> BoxFuture<'a, Result<SessionData, Error>> {
+            Box::pin(async move { Ok(self.start(&request.request)) })
+        }
+
+        fn status<'a>(
+            &'a self,
+            token: &'a SessionToken,
+        ) -> BoxFuture<'a, Result<SessionStatus, Error>> {
+            Box::pin(async move { self.with_session(token, |session| Ok(session.status.clone())) })
+        }
+
+        fn result<'a>(
+            &'a self,
+            token: &'a SessionToken,
+        ) -> BoxFuture<'a, Result<SessionResult, Error>> {
+            Box::pin(async move {
+                self.with_session(token, |session| {
+                    check_finished(&session.status)?;
+                    session
+                        .result
+                        .clone()
+                        .ok_or_else(|| Error::SessionNotFinished(session.status.clone()))
+                })
+            })
+        }
+
+        fn cancel<'a>(&'a self, token: &'a SessionToken) -> BoxFuture<'a, Result<(), Error>> {
+            Box::pin(async move {
+                self.with_session(token, |session| {
+                    session.status = SessionStatus::Cancelled;
+                    Ok(())
+                })
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::IrmaClientApi;
+    use crate::IrmaClient;
+
+    #[test]
+    fn test_object_safe() {
+        let _client: Arc<dyn IrmaClientApi> =
+            Arc::new(IrmaClient::new("http://localhost:8088/").unwrap());
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn test_fake_client() {
+        use super::fake::FakeIrmaClient;
+        use crate::{
+            AttributeRequest, DisclosureRequestBuilder, Error, SessionResult, SessionStatus,
+            SessionType,
+        };
+
+        let fake = Arc::new(FakeIrmaClient::new());
+        let client: Arc<dyn IrmaClientApi> = fake.clone();
+
+        let request = DisclosureRequestBuilder::new()
+            .add_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]])
+            .build();
+        let session = client.request(&request).await.unwrap();
+        assert_eq!(session.session_ptr.irmaqr, SessionType::Disclosing);
+        assert_eq!(fake.session_request(&session.token), Some(request));
+        assert_eq!(
+            client.status(&session.token).await.unwrap(),
+            SessionStatus::Initialized
+        );
+        assert!(matches!(
+            client.result(&session.token).await,
+            Err(Error::SessionNotFinished(SessionStatus::Initialized))
+        ));
+
+        let result = SessionResult {
+            token: session.token.clone(),
+            sessiontype: SessionType::Disclosing,
+            status: SessionStatus::Done,
+            proof_status: None,
+            disclosed: vec![],
+            signature: None,
+            next_session: None,
+        };
+        fake.set_result(&session.token, result.clone());
+        assert_eq!(client.result(&session.token).await.unwrap(), result);
+
+        client.cancel(&session.token).await.unwrap();
+        assert!(matches!(
+            client.result(&session.token).await,
+            Err(Error::SessionCancelled)
+        ));
+    }
+}
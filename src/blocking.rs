@@ -0,0 +1,541 @@
+//! Synchronous variant of the irma client, for use outside of async code.
+//! Requests, results and errors are shared with the asynchronous [crate::IrmaClient].
+//! Requestor JWTs can only be signed with keys held by the client itself, as custom
+//! [RequestSigner](crate::RequestSigner)s are asynchronous.
+
+use std::{path::Path, sync::Arc, time::Duration};
+
+use reqwest::{
+    blocking::{Client, RequestBuilder, Response},
+    redirect::Policy,
+    Url,
+};
+
+use crate::{
+    irmaclient::{
+        check_finished, check_status, parse_base_url, recognize_unknown_session, AuthMethod,
+        BasicSecret, IntoServerUrl, TokenSecret,
+    },
+    jwt::{RequestJwtSigner, SigningKey},
+    tokenfile::TokenFile,
+    Error, ExtendedIrmaRequest, IrmaRequest, ReloadPolicy, RemoteError, SessionData, SessionResult,
+    SessionStatus, SessionToken, StartSessionRequest,
+};
+
+/// Blocking client for interacting with an irma server
+#[derive(Debug, Clone)]
+pub struct IrmaClient {
+    url: Url,
+    client: Client,
+    authmethod: AuthMethod,
+    basic: Option<BasicSecret>,
+}
+
+impl IrmaClient {
+    /// Create a new client without any authentication or other special options
+    pub fn new(url: impl IntoServerUrl) -> Result<IrmaClient, Error> {
+        let mut url = parse_base_url(url)?;
+        let basic = BasicSecret::take_from_url(&mut url);
+        let client = Client::builder()
+            .redirect(redirect_policy(basic.is_some()))
+            .build()?;
+        Ok(IrmaClient {
+            url,
+            client,
+            authmethod: AuthMethod::None,
+            basic,
+        })
+    }
+
+    fn endpoint(&self, path: &str) -> Result<Url, Error> {
+        Ok(self.url.join(path)?)
+    }
+
+    fn authorize(&self, req: RequestBuilder) -> Result<RequestBuilder, Error> {
+        let req = match &self.basic {
+            Some(basic) => req.basic_auth(&basic.user, basic.password.as_ref()),
+            None => req,
+        };
+        Ok(match &self.authmethod {
+            // Signed requests authenticate themselves
+            AuthMethod::None | AuthMethod::Jwt(_) => req,
+            AuthMethod::Token(secret) => req.header(&secret.header, secret.header_value()?),
+            AuthMethod::TokenFile(file) => {
+                req.header(reqwest::header::AUTHORIZATION, file.header_value_blocking())
+            }
+        })
+    }
+
+    /// Start an IRMA session with the given request
+    pub fn request(&self, request: &IrmaRequest) -> Result<SessionData, Error> {
+        self.start_session(request)
+    }
+
+    /// Start an IRMA session with the given extended request (note: this interface is unstable, and might change significantly in the future)
+    pub fn request_extended(&self, request: &ExtendedIrmaRequest) -> Result<SessionData, Error> {
+        self.start_session(request)
+    }
+
+    fn start_session(&self, request: &impl StartSessionRequest) -> Result<SessionData, Error> {
+        request.check_options()?;
+        let req = self.client.post(self.endpoint("session")?);
+        let req = match &self.authmethod {
+            AuthMethod::Jwt(signer) => req
+                .header(reqwest::header::CONTENT_TYPE, "text/plain")
+                .body(signer.sign_blocking(&request.to_extended())?),
+            _ => req.json(request),
+        };
+        let response = self.authorize(req)?.send()?;
+        Ok(check_response(response)?.json::<SessionData>()?)
+    }
+
+    /// Get the status of a previously started irma session
+    pub fn status(&self, token: &SessionToken) -> Result<SessionStatus, Error> {
+        let response = self
+            .authorize(
+                self.client
+                    .get(self.endpoint(&format!("session/{}/status", token.0))?),
+            )?
+            .send()?;
+        Ok(recognize_unknown_session(check_response(response), token)?.json::<SessionStatus>()?)
+    }
+
+    /// Cancel a previously started session
+    pub fn cancel(&self, token: &SessionToken) -> Result<(), Error> {
+        let response = self
+            .authorize(
+                self.client
+                    .delete(self.endpoint(&format!("session/{}", token.0))?),
+            )?
+            .send()?;
+        recognize_unknown_session(check_response(response), token)?;
+        Ok(())
+    }
+
+    /// Get the result for a previously started irma session
+    pub fn result(&self, token: &SessionToken) -> Result<SessionResult, Error> {
+        let response = self
+            .authorize(
+                self.client
+                    .get(self.endpoint(&format!("session/{}/result", token.0))?),
+            )?
+            .send()?;
+        let result =
+            recognize_unknown_session(check_response(response), token)?.json::<SessionResult>()?;
+        check_finished(&result.status)?;
+        Ok(result)
+    }
+}
+
+fn check_response(response: Response) -> Result<Response, Error> {
+    check_status(response.status(), response.headers())?;
+    if let Err(status_error) = response.error_for_status_ref() {
+        return Err(match response.json::<RemoteError>() {
+            Ok(remote) => Error::ServerError(remote),
+            Err(_) => status_error.into(),
+        });
+    }
+    Ok(response)
+}
+
+// As with the asynchronous client, authenticated clients don't follow redirects, which would send
+// their credentials to wherever the redirect points
+fn redirect_policy(authenticated: bool) -> Policy {
+    if authenticated {
+        Policy::none()
+    } else {
+        Policy::limited(10)
+    }
+}
+
+/// Builder for blocking IRMA clients
+pub struct IrmaClientBuilder {
+    url: Url,
+    authmethod: AuthMethod,
+    basic: Option<BasicSecret>,
+    client: Option<Client>,
+    timeout: Option<Duration>,
+}
+
+impl IrmaClientBuilder {
+    /// Create a new Client builder, with the given URL for the irma server
+    pub fn new(url: impl IntoServerUrl) -> Result<IrmaClientBuilder, Error> {
+        let mut url = parse_base_url(url)?;
+        let basic = BasicSecret::take_from_url(&mut url);
+        Ok(IrmaClientBuilder {
+            url,
+            authmethod: AuthMethod::None,
+            basic,
+            client: None,
+            timeout: None,
+        })
+    }
+
+    /// Use the given http client for communicating with the irma server, instead of creating a new one.
+    /// The timeout is then that of the given client, so setting it on this builder as well fails
+    /// with [Error::ConflictingHttpClient] when building the client.
+    pub fn with_http_client(mut self, client: Client) -> IrmaClientBuilder {
+        self.client = Some(client);
+        self
+    }
+
+    /// Set the timeout for individual requests to the irma server (default 30 seconds).
+    pub fn timeout(mut self, timeout: Duration) -> IrmaClientBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Enable token authentication for the client, using the provided token
+    pub fn token_authentication(mut self, token: String) -> IrmaClientBuilder {
+        self.authmethod = AuthMethod::Token(TokenSecret::new(token));
+        self
+    }
+
+    /// Enable token authentication for the client, sending the token in the given header with an
+    /// optional scheme prefix, as with
+    /// [crate::IrmaClientBuilder::token_authentication_with].
+    pub fn token_authentication_with(
+        mut self,
+        token: String,
+        header: &str,
+        scheme: Option<&str>,
+    ) -> IrmaClientBuilder {
+        self.authmethod = AuthMethod::Token(TokenSecret {
+            token,
+            header: header.into(),
+            scheme: scheme.map(|scheme| scheme.into()),
+        });
+        self
+    }
+
+    /// Enable token authentication for the client, reading the token from the file at the given
+    /// path, as with [crate::IrmaClientBuilder::token_authentication_from_file].
+    pub fn token_authentication_from_file(
+        mut self,
+        path: impl AsRef<Path>,
+        reload: ReloadPolicy,
+    ) -> IrmaClientBuilder {
+        self.authmethod = AuthMethod::TokenFile(Arc::new(TokenFile::new(
+            path.as_ref().to_path_buf(),
+            reload,
+        )));
+        self
+    }
+
+    /// Authenticate as the named requestor, by signing session requests as HS256 JWTs with the
+    /// given HMAC key
+    pub fn hmac_authentication(
+        mut self,
+        requestor_name: String,
+        key: Vec<u8>,
+    ) -> IrmaClientBuilder {
+        self.authmethod = AuthMethod::Jwt(RequestJwtSigner::with_key(
+            requestor_name,
+            SigningKey::Hmac(key),
+        ));
+        self
+    }
+
+    /// Authenticate as the named requestor, by signing session requests as RS256 JWTs with the
+    /// given PEM encoded RSA private key
+    #[cfg(feature = "jwt")]
+    pub fn rsa_authentication(mut self, requestor_name: String, pem: &[u8]) -> IrmaClientBuilder {
+        self.authmethod = AuthMethod::Jwt(RequestJwtSigner::with_key(
+            requestor_name,
+            SigningKey::Rsa(pem.to_vec()),
+        ));
+        self
+    }
+
+    /// Authenticate as the named requestor, by signing session requests as ES256 JWTs with the
+    /// given PEM encoded (PKCS#8) ECDSA P-256 private key
+    #[cfg(feature = "jwt")]
+    pub fn ecdsa_authentication(mut self, requestor_name: String, pem: &[u8]) -> IrmaClientBuilder {
+        self.authmethod = AuthMethod::Jwt(RequestJwtSigner::with_key(
+            requestor_name,
+            SigningKey::Ecdsa(pem.to_vec()),
+        ));
+        self
+    }
+
+    /// Send the given credentials as http basic authentication with every request, replacing
+    /// any credentials in the url
+    pub fn basic_authentication(mut self, user: &str, password: Option<&str>) -> IrmaClientBuilder {
+        self.basic = Some(BasicSecret {
+            user: user.into(),
+            password: password.map(|password| password.into()),
+        });
+        self
+    }
+
+    /// Construct the actual IrmaClient. Unless a custom http client is used, clients with any
+    /// authentication don't follow redirects, which then result in an
+    /// [Error::UnexpectedRedirect].
+    pub fn build(self) -> Result<IrmaClient, Error> {
+        match &self.authmethod {
+            AuthMethod::None => {}
+            AuthMethod::Token(secret) => {
+                secret.validate()?;
+                if self.basic.is_some() && secret.header.eq_ignore_ascii_case("authorization") {
+                    return Err(Error::ConflictingAuthentication);
+                }
+            }
+            AuthMethod::TokenFile(file) => {
+                file.load()?;
+                if self.basic.is_some() {
+                    return Err(Error::ConflictingAuthentication);
+                }
+            }
+            AuthMethod::Jwt(signer) => signer.validate()?,
+        }
+        let client = match (self.client, self.timeout) {
+            (Some(_), Some(_)) => return Err(Error::ConflictingHttpClient("timeout".into())),
+            (Some(client), None) => client,
+            (None, timeout) => {
+                let authenticated =
+                    self.basic.is_some() || !matches!(self.authmethod, AuthMethod::None);
+                Client::builder()
+                    .timeout(timeout.unwrap_or(Duration::from_secs(30)))
+                    .redirect(redirect_policy(authenticated))
+                    .build()?
+            }
+        };
+        Ok(IrmaClient {
+            url: self.url,
+            client,
+            authmethod: self.authmethod,
+            basic: self.basic,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{body_json, header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use std::time::Duration;
+
+    use super::{IrmaClient, IrmaClientBuilder};
+    use crate::{AttributeRequest, DisclosureRequestBuilder, Error, SessionStatus, SessionToken};
+
+    #[tokio::test]
+    async fn test_blocking_client() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/session"))
+            .and(header("Authorization", "secret"))
+            .and(body_json(serde_json::json!({
+                "@context": "https://irma.app/ld/request/disclosure/v2",
+                "disclose": [[["a.b.c.d"]]],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sessionPtr": {"u": "http://irma.test/irma/session/client", "irmaqr": "disclosing"},
+                "token": "blocking",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/session/blocking/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json("INITIALIZED"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/session/blocking/result"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "token": "blocking",
+                "type": "disclosing",
+                "status": "CANCELLED",
+            })))
+            .mount(&server)
+            .await;
+
+        let uri = server.uri();
+        tokio::task::spawn_blocking(move || {
+            let client = IrmaClientBuilder::new(&uri)
+                .unwrap()
+                .token_authentication("secret".into())
+                .build()
+                .unwrap();
+            let request = DisclosureRequestBuilder::new()
+                .add_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]])
+                .build();
+            let session = client.request(&request).unwrap();
+            assert_eq!(session.token, SessionToken("blocking".into()));
+            assert_eq!(
+                client.status(&session.token).unwrap(),
+                SessionStatus::Initialized
+            );
+            assert!(matches!(
+                client.result(&session.token),
+                Err(Error::SessionCancelled)
+            ));
+
+            let result = IrmaClientBuilder::new(&uri)
+                .unwrap()
+                .with_http_client(reqwest::blocking::Client::new())
+                .timeout(std::time::Duration::from_secs(5))
+                .build();
+            assert!(matches!(result, Err(Error::ConflictingHttpClient(_))));
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_blocking_redirect_and_busy() {
+        let server = MockServer::start().await;
+        let target = format!("{}/target/session/moved/status", server.uri());
+        Mock::given(method("GET"))
+            .and(path("/session/moved/status"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", target.as_str()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/target/session/moved/status"))
+            .and(header("Authorization", "secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json("DONE"))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/target/session/moved/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json("DONE"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/session/busy/status"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "7"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/session/unavailable/status"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let uri = server.uri();
+        tokio::task::spawn_blocking(move || {
+            let client = IrmaClientBuilder::new(&uri)
+                .unwrap()
+                .token_authentication("secret".into())
+                .build()
+                .unwrap();
+            match client.status(&SessionToken("moved".into())) {
+                Err(Error::UnexpectedRedirect { location }) => {
+                    assert_eq!(location, Some(target.clone()))
+                }
+                result => panic!("Unexpected result {:?}", result),
+            }
+            match client.status(&SessionToken("busy".into())) {
+                Err(Error::ServerBusy { retry_after }) => {
+                    assert_eq!(retry_after, Some(Duration::from_secs(7)))
+                }
+                result => panic!("Unexpected result {:?}", result),
+            }
+            assert!(matches!(
+                client.status(&SessionToken("unavailable".into())),
+                Err(Error::ServerBusy { retry_after: None })
+            ));
+
+            let client =
+                IrmaClient::new(format!("{}/", uri.replace("://", "://user:pass@"))).unwrap();
+            assert!(matches!(
+                client.status(&SessionToken("moved".into())),
+                Err(Error::UnexpectedRedirect { .. })
+            ));
+
+            let client = IrmaClient::new(&uri).unwrap();
+            assert_eq!(
+                client.status(&SessionToken("moved".into())).unwrap(),
+                SessionStatus::Done
+            );
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_blocking_authentication() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/session"))
+            .and(header("Content-Type", "text/plain"))
+            .and(|request: &wiremock::Request| {
+                let body = String::from_utf8_lossy(&request.body);
+                let claims: serde_json::Value = match crate::jwt::decode_payload(&body) {
+                    Ok(claims) => claims,
+                    Err(_) => return false,
+                };
+                body.starts_with("eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.")
+                    && claims["iss"] == "testsp"
+                    && claims["sub"] == "verification_request"
+                    && claims["sprequest"]["request"]["disclose"][0][0][0] == "a.b.c.d"
+            })
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sessionPtr": {"u": "http://irma.test/irma/session/client", "irmaqr": "disclosing"},
+                "token": "signed",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/session/custom"))
+            .and(header("X-Irma-Token", "Bearer secret"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/session/file"))
+            .and(header("Authorization", "fromfile"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let uri = server.uri();
+        tokio::task::spawn_blocking(move || {
+            let client = IrmaClientBuilder::new(&uri)
+                .unwrap()
+                .hmac_authentication("testsp".into(), b"irma-requestor-hmac-key".to_vec())
+                .build()
+                .unwrap();
+            assert!(!format!("{:?}", client).contains("hmac-key"));
+            let request = DisclosureRequestBuilder::new()
+                .add_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]])
+                .build();
+            let session = client.request(&request).unwrap();
+            assert_eq!(session.token, SessionToken("signed".into()));
+
+            let client = IrmaClientBuilder::new(&uri)
+                .unwrap()
+                .token_authentication_with("secret".into(), "X-Irma-Token", Some("Bearer"))
+                .build()
+                .unwrap();
+            client.cancel(&SessionToken("custom".into())).unwrap();
+
+            let token_file = std::env::temp_dir()
+                .join(format!("irma-blocking-client-token-{}", std::process::id()));
+            std::fs::write(&token_file, "fromfile\n").unwrap();
+            let client = IrmaClientBuilder::new(&uri)
+                .unwrap()
+                .token_authentication_from_file(&token_file, crate::ReloadPolicy::Never)
+                .build()
+                .unwrap();
+            std::fs::remove_file(&token_file).unwrap();
+            client.cancel(&SessionToken("file".into())).unwrap();
+
+            let result = IrmaClientBuilder::new(&uri)
+                .unwrap()
+                .token_authentication_from_file(&token_file, crate::ReloadPolicy::Never)
+                .build();
+            assert!(matches!(result, Err(Error::InvalidTokenFile(_))));
+        })
+        .await
+        .unwrap();
+    }
+}
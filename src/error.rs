@@ -1,16 +1,300 @@
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
 use thiserror::Error as ThisError;
 
+/// Error information returned by the irma server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteError {
+    /// HTTP status of the response
+    #[serde(default)]
+    pub status: u16,
+    /// Name of the error (e.g. SESSION_UNKNOWN)
+    #[serde(rename = "error", default)]
+    pub name: String,
+    /// Human readable description of the error
+    #[serde(default)]
+    pub description: String,
+    /// Additional details on the error, if any
+    #[serde(default)]
+    pub message: String,
+}
+
+impl Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.description)?;
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        Ok(())
+    }
+}
+
 /// Errors resulting from IrmaClient operations
 #[derive(Debug, ThisError)]
 pub enum Error {
     #[error("Invalid URL: {0}")]
     InvalidUrl(#[from] url::ParseError),
+    /// The URL parsed correctly, but cannot be used by the client. Unlike [Error::InvalidUrl],
+    /// which only carries the errors of the URL parser itself, this says what is wrong with it.
+    #[error("Unsupported URL: {0}")]
+    UnsupportedUrl(UnsupportedUrlReason),
     #[error("Network error: {0}")]
-    NetworkError(#[from] reqwest::Error),
+    NetworkError(reqwest::Error),
+    #[error("Request to irma server timed out: {0}")]
+    RequestTimedOut(reqwest::Error),
+    #[error("Invalid certificate: {0}")]
+    InvalidCertificate(reqwest::Error),
+    #[error("Invalid client certificate or key: {0}")]
+    InvalidIdentity(String),
+    #[error("Invalid requestor signing key: {0}")]
+    InvalidSigningKey(String),
+    #[error("Signing requestor JWT failed: {0}")]
+    SigningFailed(Box<dyn std::error::Error + Send + Sync>),
+    #[error("Claim {0} of requestor JWTs is reserved")]
+    ReservedJwtClaim(String),
+    #[error("Callback URL is not an absolute http(s) URL: {0}")]
+    InvalidCallbackUrl(String),
+    #[error("Invalid host for session pointers, expected host[:port]: {0}")]
+    InvalidHost(String),
+    #[error("Invalid session request: {}", join_issues(.0))]
+    InvalidRequest(Vec<RequestValidationError>),
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("Could not read requestor token file: {0}")]
+    InvalidTokenFile(std::io::Error),
+    #[error("Could not access signature file: {0}")]
+    SignatureFile(std::io::Error),
+    #[error("Invalid signature file: {0}")]
+    InvalidSignatureFile(serde_json::Error),
+    #[error("Invalid credential attributes: {0}")]
+    InvalidAttributes(String),
+    #[error("Attribute {0} has a nested value, only strings, numbers and booleans are supported")]
+    NestedAttributeValue(String),
+    #[error("Could not serialize message to be signed: {0}")]
+    InvalidMessage(serde_json::Error),
+    #[error("Could not serialize session request: {0}")]
+    RequestSerialization(serde_json::Error),
+    #[error("Issuance time of the credential to revoke lies before the Unix epoch")]
+    IssuedBeforeEpoch,
+    #[error("Basic authentication and token authentication both use the Authorization header")]
+    ConflictingAuthentication,
+    #[error("Option {0} of the client builder has no effect on a custom http client")]
+    ConflictingHttpClient(String),
+    #[error("Client follows redirects by default, so it cannot send authentication")]
+    RedirectsWithAuthentication,
+    #[error("Irma server error: {0}")]
+    ServerError(RemoteError),
     #[error("Irma session cancelled")]
     SessionCancelled,
     #[error("Irma session timed out")]
     SessionTimedOut,
     #[error("Irma session not finished")]
     SessionNotFinished(super::sessionresult::SessionStatus),
+    #[error("Irma session unknown")]
+    SessionUnknown(super::irmaclient::SessionToken),
+    #[error("Irma server has no JWT signing key configured")]
+    NoPublicKey,
+    #[error("Operation was cancelled")]
+    Cancelled,
+    #[error("Deadline exceeded while waiting for irma session")]
+    DeadlineExceeded,
+    #[error("Invalid status event: {0}")]
+    InvalidStatusEvent(serde_json::Error),
+    #[error("Invalid JWT received from irma server")]
+    InvalidJwt,
+    #[error("JWT must consist of three segments, found {0}")]
+    JwtSegmentCount(usize),
+    #[error("Invalid base64 encoding of JWT")]
+    JwtEncoding,
+    #[error("Invalid claims in JWT: {0}")]
+    JwtClaims(serde_json::Error),
+    #[error("Invalid public key for verifying JWTs: {0}")]
+    InvalidVerificationKey(String),
+    #[error("Signature of JWT is invalid")]
+    BadSignature,
+    #[error("JWT has expired")]
+    Expired,
+    #[error("JWT was issued by an unexpected issuer")]
+    WrongIssuer,
+    #[error("Connection for irma status events was lost")]
+    StatusEventsDisconnected,
+    #[error("Chained irma session {token:?} not finished, status {status:?}")]
+    ChainedSessionNotFinished {
+        token: super::irmaclient::SessionToken,
+        status: super::sessionresult::SessionStatus,
+    },
+    #[error("Chain of irma sessions longer than {0}")]
+    SessionChainTooLong(usize),
+    #[error("Invalid response from irma server (status {status}): {source}, response body: {body_snippet}")]
+    InvalidResponse {
+        status: u16,
+        body_snippet: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Unexpected redirect from irma server to {location:?}")]
+    UnexpectedRedirect { location: Option<String> },
+    #[error("Irma server rejected the authentication of the client")]
+    Unauthorized,
+    #[error("Irma server is busy, retry after {retry_after:?}")]
+    ServerBusy {
+        retry_after: Option<std::time::Duration>,
+    },
+    #[error("Request interceptor failed: {0}")]
+    InterceptorFailed(Box<dyn std::error::Error + Send + Sync>),
+    #[error("Request {index} of batch failed: {source}")]
+    BatchRequestFailed {
+        index: usize,
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+/// Reasons why a session request is invalid, found by the request builders or by
+/// [IrmaRequest::validate](crate::IrmaRequest::validate). Disjunctions are indexed in the order
+/// of the `disclose` list of the request, and conjunctions in the order of the options of their
+/// disjunction, both counting from 0.
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+pub enum RequestValidationError {
+    #[error("Request does not ask for any attributes, add at least one disjunction")]
+    NoDisjunctions,
+    #[error("Issuance request does not contain any credentials, add at least one credential")]
+    NoCredentials,
+    #[error("Disjunction {disjunction} of the request has no options to choose from")]
+    EmptyDisjunction { disjunction: usize },
+    #[error(
+        "Option {conjunction} of disjunction {disjunction} of the request does not contain any attributes"
+    )]
+    EmptyInnerConjunction {
+        disjunction: usize,
+        conjunction: usize,
+    },
+    #[error(
+        "Option {conjunction} of disjunction {disjunction} of the request asks for {attribute} more than once"
+    )]
+    RepeatedAttribute {
+        disjunction: usize,
+        conjunction: usize,
+        attribute: String,
+    },
+    #[error("Option {conjunction} of disjunction {disjunction} of the request requires both a specific value and a non-empty value for {attribute}")]
+    ValueAndNotNull {
+        disjunction: usize,
+        conjunction: usize,
+        attribute: String,
+    },
+    #[error(
+        "Return URL {0} is not an absolute http(s) URL, use return_url_unchecked for other URLs"
+    )]
+    InvalidReturnUrl(String),
+    #[error("Return URL of the request is set to {second}, but was already set to {first}")]
+    ReturnUrlSetTwice { first: String, second: String },
+    #[error(
+        "Label refers to disjunction {index}, but the request has only {disjunctions} disjunctions"
+    )]
+    LabelOutOfRange { index: usize, disjunctions: usize },
+    #[error("Invalid credential type: {0}")]
+    InvalidCredentialType(InvalidIdentifier),
+    #[error("Validity of credential {0} lies before the Unix epoch")]
+    ValidityBeforeEpoch(String),
+    #[error("Attribute {attribute} of credential {credential} is set more than once")]
+    DuplicateAttribute {
+        credential: String,
+        attribute: String,
+    },
+}
+
+/// Reasons why a URL that parsed correctly cannot be used by the client
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+pub enum UnsupportedUrlReason {
+    #[error("URL cannot be used as a base")]
+    CannotBeABase,
+    #[error("scheme {0} is not supported, expected http or https")]
+    Scheme(String),
+}
+
+/// Reasons why a session result does not satisfy a [FreshnessPolicy](crate::FreshnessPolicy)
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+pub enum FreshnessViolation {
+    #[error("Session result contains attributes of expired credentials")]
+    Expired,
+    #[error("Credential of attribute {identifier} was issued {age:?} ago, more than the allowed {max_age:?}")]
+    TooOld {
+        identifier: String,
+        age: std::time::Duration,
+        max_age: std::time::Duration,
+    },
+    #[error("Issuance time of the credential of attribute {0} is unknown")]
+    UnknownIssuanceTime(String),
+}
+
+/// Reasons why a required attribute cannot be taken from a session result, see
+/// [SessionResult::require_attribute](crate::SessionResult::require_attribute)
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+pub enum ResultError {
+    #[error("Cannot use attribute {identifier}, session has status {status:?} and proof status {proof_status:?}")]
+    SessionNotValid {
+        identifier: String,
+        status: super::sessionresult::SessionStatus,
+        proof_status: Option<super::sessionresult::ProofStatus>,
+    },
+    #[error("Attribute {0} was not disclosed")]
+    NotDisclosed(String),
+    #[error("Attribute {0} was disclosed without a value")]
+    NullValue(String),
+}
+
+/// Reasons why a string is not a valid identifier of an attribute or credential type
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+pub enum InvalidIdentifier {
+    #[error(
+        "Identifier {identifier:?} consists of {found} dot-separated segments, expected {expected}"
+    )]
+    SegmentCount {
+        identifier: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Identifier {0:?} contains an empty segment")]
+    EmptySegment(String),
+    #[error("Identifier {0:?} contains whitespace")]
+    Whitespace(String),
+}
+
+/// Reasons why loading a session request from JSON failed, see
+/// [IrmaRequest::from_json](crate::IrmaRequest::from_json)
+#[derive(Debug, ThisError)]
+pub enum RequestLoadError {
+    #[error("Could not read request file {}: {source}", .path.display())]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Request is not valid JSON: {0}")]
+    Syntax(serde_json::Error),
+    /// The JSON does not describe a request. The pointer (RFC 6901) indicates the value at which
+    /// the problem was found, which is empty for the request itself.
+    #[error("Invalid request at {pointer:?}: {message}")]
+    Invalid { pointer: String, message: String },
+    #[error("Invalid session request: {}", join_issues(.0))]
+    InvalidRequest(Vec<RequestValidationError>),
+}
+
+fn join_issues(issues: &[RequestValidationError]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Error::RequestTimedOut(e)
+        } else {
+            Error::NetworkError(e)
+        }
+    }
 }
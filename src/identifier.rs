@@ -0,0 +1,295 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AttributeRequest, InvalidIdentifier};
+
+// Check that the identifier consists of the expected number of non-empty, dot-separated segments
+fn check_segments(identifier: &str, expected: usize) -> Result<(), InvalidIdentifier> {
+    if identifier.contains(char::is_whitespace) {
+        return Err(InvalidIdentifier::Whitespace(identifier.into()));
+    }
+    let found = identifier.split('.').count();
+    if found != expected {
+        return Err(InvalidIdentifier::SegmentCount {
+            identifier: identifier.into(),
+            expected,
+            found,
+        });
+    }
+    if identifier.split('.').any(str::is_empty) {
+        return Err(InvalidIdentifier::EmptySegment(identifier.into()));
+    }
+    Ok(())
+}
+
+/// Identifier of an attribute type, of the form `scheme.issuer.credential.attribute`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AttributeIdentifier(String);
+
+impl AttributeIdentifier {
+    fn segment(&self, index: usize) -> &str {
+        self.0.split('.').nth(index).unwrap_or_default()
+    }
+
+    /// Scheme manager of the attribute, e.g. `pbdf`
+    pub fn scheme(&self) -> &str {
+        self.segment(0)
+    }
+
+    /// Issuer of the credential containing the attribute, e.g. `sidn-pbdf`
+    pub fn issuer(&self) -> &str {
+        self.segment(1)
+    }
+
+    /// Name of the credential containing the attribute, e.g. `email`
+    pub fn credential(&self) -> &str {
+        self.segment(2)
+    }
+
+    /// Name of the attribute within its credential, e.g. `email`
+    pub fn attribute(&self) -> &str {
+        self.segment(3)
+    }
+
+    /// Identifier of the credential type containing the attribute, e.g. `pbdf.sidn-pbdf.email`
+    pub fn credential_identifier(&self) -> CredentialIdentifier {
+        let end = self.0.rfind('.').unwrap_or_default();
+        CredentialIdentifier(self.0[..end].into())
+    }
+
+    /// The full identifier
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for AttributeIdentifier {
+    type Err = InvalidIdentifier;
+
+    fn from_str(identifier: &str) -> Result<Self, Self::Err> {
+        check_segments(identifier, 4)?;
+        Ok(AttributeIdentifier(identifier.into()))
+    }
+}
+
+impl TryFrom<String> for AttributeIdentifier {
+    type Error = InvalidIdentifier;
+
+    fn try_from(identifier: String) -> Result<Self, Self::Error> {
+        check_segments(&identifier, 4)?;
+        Ok(AttributeIdentifier(identifier))
+    }
+}
+
+impl Display for AttributeIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<AttributeIdentifier> for String {
+    fn from(identifier: AttributeIdentifier) -> Self {
+        identifier.0
+    }
+}
+
+impl From<AttributeIdentifier> for AttributeRequest {
+    fn from(identifier: AttributeIdentifier) -> Self {
+        AttributeRequest::Simple(identifier.0)
+    }
+}
+
+/// Identifier of a credential type, of the form `scheme.issuer.credential`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CredentialIdentifier(String);
+
+impl CredentialIdentifier {
+    // Identifier that is not checked, for builders that report its problems when building
+    pub(crate) fn unchecked(identifier: String) -> CredentialIdentifier {
+        CredentialIdentifier(identifier)
+    }
+
+    fn segment(&self, index: usize) -> &str {
+        self.0.split('.').nth(index).unwrap_or_default()
+    }
+
+    /// Scheme manager of the credential type, e.g. `pbdf`
+    pub fn scheme(&self) -> &str {
+        self.segment(0)
+    }
+
+    /// Issuer of the credential type, e.g. `sidn-pbdf`
+    pub fn issuer(&self) -> &str {
+        self.segment(1)
+    }
+
+    /// Name of the credential type within its issuer, e.g. `email`
+    pub fn credential(&self) -> &str {
+        self.segment(2)
+    }
+
+    /// Identifier of the attribute with the given name in this credential type
+    ///
+    /// # Panics
+    /// Panics when the name is empty or contains dots or whitespace
+    pub fn attribute(&self, name: &str) -> AttributeIdentifier {
+        format!("{}.{}", self.0, name)
+            .parse()
+            .unwrap_or_else(|e| panic!("Invalid attribute name {:?}: {}", name, e))
+    }
+
+    /// The full identifier
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for CredentialIdentifier {
+    type Err = InvalidIdentifier;
+
+    fn from_str(identifier: &str) -> Result<Self, Self::Err> {
+        check_segments(identifier, 3)?;
+        Ok(CredentialIdentifier(identifier.into()))
+    }
+}
+
+impl TryFrom<String> for CredentialIdentifier {
+    type Error = InvalidIdentifier;
+
+    fn try_from(identifier: String) -> Result<Self, Self::Error> {
+        check_segments(&identifier, 3)?;
+        Ok(CredentialIdentifier(identifier))
+    }
+}
+
+impl Display for CredentialIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<CredentialIdentifier> for String {
+    fn from(identifier: CredentialIdentifier) -> Self {
+        identifier.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AttributeIdentifier, CredentialIdentifier};
+    use crate::{AttributeRequest, InvalidIdentifier};
+
+    #[test]
+    fn test_attribute_identifier() {
+        let id: AttributeIdentifier = "pbdf.sidn-pbdf.email.email".parse().unwrap();
+        assert_eq!(id.scheme(), "pbdf");
+        assert_eq!(id.issuer(), "sidn-pbdf");
+        assert_eq!(id.credential(), "email");
+        assert_eq!(id.attribute(), "email");
+        assert_eq!(id.credential_identifier().as_str(), "pbdf.sidn-pbdf.email");
+        assert_eq!(id.to_string(), "pbdf.sidn-pbdf.email.email");
+        assert_eq!(
+            AttributeRequest::non_null(id.clone()),
+            AttributeRequest::non_null("pbdf.sidn-pbdf.email.email")
+        );
+        assert_eq!(
+            AttributeRequest::with_value(id.clone(), "yes"),
+            AttributeRequest::with_value("pbdf.sidn-pbdf.email.email", "yes")
+        );
+        assert_eq!(
+            AttributeRequest::from(id),
+            AttributeRequest::Simple("pbdf.sidn-pbdf.email.email".into())
+        );
+
+        assert_eq!(
+            "pbdf.sidn-pbdf.email".parse::<AttributeIdentifier>(),
+            Err(InvalidIdentifier::SegmentCount {
+                identifier: "pbdf.sidn-pbdf.email".into(),
+                expected: 4,
+                found: 3
+            })
+        );
+        assert_eq!(
+            "pbdf..email.email".parse::<AttributeIdentifier>(),
+            Err(InvalidIdentifier::EmptySegment("pbdf..email.email".into()))
+        );
+        assert_eq!(
+            "pbdf.sidn-pbdf.email.email ".parse::<AttributeIdentifier>(),
+            Err(InvalidIdentifier::Whitespace(
+                "pbdf.sidn-pbdf.email.email ".into()
+            ))
+        );
+        assert!("".parse::<AttributeIdentifier>().is_err());
+    }
+
+    #[test]
+    fn test_attribute_identifier_serde() {
+        let id: AttributeIdentifier = "irma-demo.MijnOverheid.root.BSN".parse().unwrap();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"irma-demo.MijnOverheid.root.BSN\"");
+        assert_eq!(
+            serde_json::from_str::<AttributeIdentifier>(&json).unwrap(),
+            id
+        );
+        assert!(serde_json::from_str::<AttributeIdentifier>("\"irma-demo.MijnOverheid\"").is_err());
+    }
+
+    #[test]
+    fn test_credential_identifier() {
+        let id: CredentialIdentifier = "pbdf.sidn-pbdf.email".parse().unwrap();
+        assert_eq!(id.scheme(), "pbdf");
+        assert_eq!(id.issuer(), "sidn-pbdf");
+        assert_eq!(id.credential(), "email");
+        assert_eq!(id.to_string(), "pbdf.sidn-pbdf.email");
+        assert_eq!(
+            CredentialIdentifier::try_from(String::from("pbdf.sidn-pbdf.email")),
+            Ok(id.clone())
+        );
+
+        let attr = id.attribute("email");
+        assert_eq!(attr.as_str(), "pbdf.sidn-pbdf.email.email");
+        assert_eq!(attr.credential_identifier(), id);
+
+        assert_eq!(
+            "pbdf.sidn-pbdf.email.email".parse::<CredentialIdentifier>(),
+            Err(InvalidIdentifier::SegmentCount {
+                identifier: "pbdf.sidn-pbdf.email.email".into(),
+                expected: 3,
+                found: 4
+            })
+        );
+        assert_eq!(
+            "pbdf.sidn-pbdf.".parse::<CredentialIdentifier>(),
+            Err(InvalidIdentifier::EmptySegment("pbdf.sidn-pbdf.".into()))
+        );
+    }
+
+    #[test]
+    fn test_credential_identifier_from_invalid() {
+        assert!(matches!(
+            CredentialIdentifier::try_from(String::from("pbdf.sidn-pbdf.email.email")),
+            Err(InvalidIdentifier::SegmentCount { found: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn test_credential_identifier_serde() {
+        let id: CredentialIdentifier = "irma-demo.MijnOverheid.root".parse().unwrap();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"irma-demo.MijnOverheid.root\"");
+        assert_eq!(
+            serde_json::from_str::<CredentialIdentifier>(&json).unwrap(),
+            id
+        );
+        assert!(serde_json::from_str::<CredentialIdentifier>(
+            "\"irma-demo.MijnOverheid.root.BSN\""
+        )
+        .is_err());
+    }
+}
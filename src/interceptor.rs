@@ -0,0 +1,183 @@
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::SystemTime,
+};
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method, Url,
+};
+
+use crate::{BoxFuture, EndpointKind};
+
+/// Error returned by an [Interceptor] to abort a call
+pub type InterceptorError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Request about to be sent to the irma server, as seen by an [Interceptor]
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct RequestParts {
+    /// The kind of endpoint the request is made to
+    pub endpoint: EndpointKind,
+    /// Http method of the request
+    pub method: Method,
+    /// Full url of the request
+    pub url: Url,
+    /// Headers of the request, which the interceptor may modify
+    pub headers: HeaderMap,
+}
+
+/// Response received from the irma server, as seen by an [Interceptor]
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ResponseParts {
+    /// The kind of endpoint the request was made to
+    pub endpoint: EndpointKind,
+    /// Http status of the response
+    pub status: u16,
+    /// Headers of the response
+    pub headers: HeaderMap,
+}
+
+/// Middleware for requests to the irma server, registered with
+/// [IrmaClientBuilder::layer](crate::IrmaClientBuilder::layer).
+///
+/// Interceptors are run in the order in which they were registered, both before sending and after
+/// receiving. An error returned by an interceptor aborts the call with [Error::InterceptorFailed](crate::Error::InterceptorFailed).
+pub trait Interceptor: Send + Sync {
+    /// Called before sending a request, for example to add headers
+    fn before_request<'a>(
+        &'a self,
+        request: &'a mut RequestParts,
+    ) -> BoxFuture<'a, Result<(), InterceptorError>>;
+
+    /// Called after receiving the response to a request
+    fn after_response<'a>(
+        &'a self,
+        _response: &'a ResponseParts,
+    ) -> BoxFuture<'a, Result<(), InterceptorError>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+// Wrapper to allow deriving Debug on the client
+#[derive(Clone, Default)]
+pub(crate) struct Interceptors(pub(crate) Vec<Arc<dyn Interceptor>>);
+
+impl Debug for Interceptors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Interceptors")
+            .field("count", &self.0.len())
+            .finish()
+    }
+}
+
+impl Interceptors {
+    pub(crate) async fn before_request(
+        &self,
+        endpoint: EndpointKind,
+        request: &mut reqwest::Request,
+    ) -> Result<(), crate::Error> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let mut parts = RequestParts {
+            endpoint,
+            method: request.method().clone(),
+            url: request.url().clone(),
+            headers: std::mem::take(request.headers_mut()),
+        };
+        for interceptor in &self.0 {
+            interceptor
+                .before_request(&mut parts)
+                .await
+                .map_err(crate::Error::InterceptorFailed)?;
+        }
+        *request.headers_mut() = parts.headers;
+        Ok(())
+    }
+
+    pub(crate) async fn after_response(
+        &self,
+        endpoint: EndpointKind,
+        response: &reqwest::Response,
+    ) -> Result<(), crate::Error> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let parts = ResponseParts {
+            endpoint,
+            status: response.status().as_u16(),
+            headers: response.headers().clone(),
+        };
+        for interceptor in &self.0 {
+            interceptor
+                .after_response(&parts)
+                .await
+                .map_err(crate::Error::InterceptorFailed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Interceptor adding a unique request id header (by default `X-Request-Id`) to every request,
+/// for correlating requests with the logs of the irma server or proxies in front of it.
+/// Requests already carrying the header are left as is.
+#[derive(Debug)]
+pub struct RequestIdInterceptor {
+    header: HeaderName,
+    prefix: String,
+    counter: AtomicU64,
+}
+
+impl RequestIdInterceptor {
+    /// Create an interceptor using the `X-Request-Id` header
+    pub fn new() -> RequestIdInterceptor {
+        RequestIdInterceptor::with_header(HeaderName::from_static("x-request-id"))
+    }
+
+    /// Create an interceptor using the given header
+    pub fn with_header(header: HeaderName) -> RequestIdInterceptor {
+        // Distinguish ids from different clients (and process runs) by their creation time
+        let created = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        RequestIdInterceptor {
+            header,
+            prefix: format!("{:x}", created),
+            counter: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Default for RequestIdInterceptor {
+    fn default() -> Self {
+        RequestIdInterceptor::new()
+    }
+}
+
+impl Interceptor for RequestIdInterceptor {
+    fn before_request<'a>(
+        &'a self,
+        request: &'a mut RequestParts,
+    ) -> BoxFuture<'a, Result<(), InterceptorError>> {
+        Box::pin(async move {
+            if !request.headers.contains_key(&self.header) {
+                let id = format!(
+                    "{}-{}",
+                    self.prefix,
+                    self.counter.fetch_add(1, Ordering::Relaxed)
+                );
+                request
+                    .headers
+                    .insert(self.header.clone(), HeaderValue::from_str(&id)?);
+            }
+            Ok(())
+        })
+    }
+}
@@ -1,16 +1,63 @@
+#[cfg(not(target_arch = "wasm32"))]
+mod api;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
 mod error;
+mod identifier;
+#[cfg(not(target_arch = "wasm32"))]
+mod interceptor;
 mod irmaclient;
+pub mod jwt;
+mod macros;
+mod observer;
 mod sessionrequest;
 mod sessionresult;
+pub mod signature;
+#[cfg(all(feature = "sse", not(target_arch = "wasm32")))]
+mod statusevents;
+#[cfg(not(target_arch = "wasm32"))]
+mod tokenfile;
 mod util;
+#[cfg(all(feature = "tracing", not(target_arch = "wasm32")))]
+mod wirelog;
 
-pub use error::Error;
-pub use irmaclient::{IrmaClient, IrmaClientBuilder, Qr, SessionData, SessionToken};
+#[cfg(all(feature = "test-util", not(target_arch = "wasm32")))]
+pub use api::fake;
+#[cfg(not(target_arch = "wasm32"))]
+pub use api::IrmaClientApi;
+pub use error::{
+    Error, FreshnessViolation, InvalidIdentifier, RemoteError, RequestLoadError,
+    RequestValidationError, ResultError, UnsupportedUrlReason,
+};
+pub use identifier::{AttributeIdentifier, CredentialIdentifier};
+#[cfg(not(target_arch = "wasm32"))]
+pub use interceptor::{
+    Interceptor, InterceptorError, RequestIdInterceptor, RequestParts, ResponseParts,
+};
+pub use irmaclient::{
+    AuthCredentials, IntoServerUrl, IrmaClient, IrmaClientBuilder, Qr, SessionData, SessionHandle,
+    SessionToken,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use irmaclient::{CancelOnDrop, PollOptions, RedirectPolicy};
+pub use jwt::{RequestSigner, SignError, Signature};
+#[cfg(feature = "jwt")]
+pub use jwt::{VerificationKey, VerificationOptions};
+pub use observer::{EndpointKind, Outcome, RequestInfo, ResponseInfo, ResponseObserver};
+#[allow(deprecated)]
+pub use sessionrequest::RawConDisCon;
 pub use sessionrequest::{
-    AttributeRequest, ConDisCon, Credential, CredentialBuilder, DisclosureRequestBuilder,
-    ExtendedIrmaRequest, IrmaRequest, IssuanceRequestBuilder, SignatureRequestBuilder,
+    all_of, any_of, one_of_sets, AttributeRequest, AttributeValue, BaseRequest, Choice, ConDisCon,
+    Credential, CredentialBuilder, DisclosureRequestBuilder, ExtendedIrmaRequest, IrmaRequest,
+    IssuanceRequestBuilder, NextSessionData, SignatureRequestBuilder, StartSessionRequest,
 };
 pub use sessionresult::{
-    AttributeStatus, DisclosedAttribute, ProofStatus, SessionResult, SessionStatus, SessionType,
+    AttributeStatus, DisclosedAttribute, FreshnessPolicy, ProofJwtClaims, ProofStatus,
+    SessionResult, SessionResultJwtClaims, SessionStatus, SessionType,
 };
-pub use util::TranslatedString;
+pub use signature::{IrmaSignature, StoredSignature};
+#[cfg(all(feature = "sse", not(target_arch = "wasm32")))]
+pub use statusevents::StatusEventsOptions;
+#[cfg(not(target_arch = "wasm32"))]
+pub use tokenfile::ReloadPolicy;
+pub use util::{canonical_json, BoxFuture, TranslatedString};
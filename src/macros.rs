@@ -0,0 +1,97 @@
+/// Construct a [ConDisCon](crate::ConDisCon) with the same nesting as the JSON form of disclosure
+/// requests in the IRMA documentation.
+///
+/// Each attribute is either an attribute identifier, requesting any value of that attribute, or
+/// `{ identifier => value }`, requesting a specific value, or `{ identifier => non_null }`,
+/// requesting any value except an empty one.
+///
+/// ```
+/// let disclose = irma::condiscon![
+///     [["pbdf.sidn-pbdf.email.email"], ["pbdf.pbdf.mobilenumber.mobilenumber"]],
+///     [
+///         ["irma-demo.MijnOverheid.ageLimits.over18", { "irma-demo.a.b.c" => "yes" }],
+///         [{ "irma-demo.x.y.z" => non_null }],
+///     ],
+/// ];
+/// assert_eq!(disclose.len(), 2);
+/// ```
+///
+/// The nesting must be exactly three levels deep:
+///
+/// ```compile_fail
+/// let disclose = irma::condiscon![["pbdf.sidn-pbdf.email.email"]];
+/// ```
+#[macro_export]
+macro_rules! condiscon {
+    ($([$([$($attr:tt),* $(,)?]),* $(,)?]),* $(,)?) => {
+        $crate::ConDisCon::from(::std::vec![
+            $(::std::vec![$($crate::attrcon![$($attr),*]),*]),*
+        ])
+    };
+}
+
+/// Construct an inner conjunction of a [ConDisCon](crate::ConDisCon), a `Vec` of
+/// [AttributeRequest](crate::AttributeRequest)s, using the attribute syntax of [condiscon]
+#[macro_export]
+macro_rules! attrcon {
+    (@attr { $attr:expr => non_null }) => {
+        $crate::AttributeRequest::non_null(::std::string::String::from($attr))
+    };
+    (@attr { $attr:expr => $value:expr }) => {
+        $crate::AttributeRequest::with_value(
+            ::std::string::String::from($attr),
+            ::std::string::String::from($value),
+        )
+    };
+    (@attr $attr:expr) => {
+        $crate::AttributeRequest::Simple(::std::string::String::from($attr))
+    };
+    ($($attr:tt),* $(,)?) => {
+        ::std::vec![$($crate::attrcon!(@attr $attr)),*]
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AttributeRequest, ConDisCon};
+
+    #[test]
+    fn test_condiscon() {
+        let disclose: ConDisCon = condiscon![
+            [["irma-demo.a.b.c"], ["irma-demo.x.y.z", { "irma-demo.p.q.r" => "value" }]],
+            [[{ "irma-demo.n.o.p" => non_null },],],
+        ];
+        assert_eq!(
+            disclose,
+            ConDisCon::from(vec![
+                vec![
+                    vec![AttributeRequest::Simple("irma-demo.a.b.c".into())],
+                    vec![
+                        AttributeRequest::Simple("irma-demo.x.y.z".into()),
+                        AttributeRequest::with_value("irma-demo.p.q.r", "value"),
+                    ],
+                ],
+                vec![vec![AttributeRequest::non_null("irma-demo.n.o.p")]],
+            ])
+        );
+        assert_eq!(
+            serde_json::to_string(&disclose).unwrap(),
+            r#"[[["irma-demo.a.b.c"],["irma-demo.x.y.z",{"type":"irma-demo.p.q.r","value":"value"}]],[[{"type":"irma-demo.n.o.p","notNull":true}]]]"#
+        );
+        assert!(condiscon![].is_empty());
+    }
+
+    #[test]
+    fn test_attrcon() {
+        let value = String::from("value");
+        assert_eq!(
+            attrcon!["a.b.c.d", { "e.f.g.h" => value }],
+            vec![
+                AttributeRequest::Simple("a.b.c.d".into()),
+                AttributeRequest::with_value("e.f.g.h", "value"),
+            ]
+        );
+        let empty: Vec<AttributeRequest> = attrcon![];
+        assert!(empty.is_empty());
+    }
+}
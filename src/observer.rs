@@ -0,0 +1,86 @@
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+/// Kind of irma server endpoint an http exchange was made with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EndpointKind {
+    /// Starting a session
+    Start,
+    /// Fetching the status of a session
+    Status,
+    /// Fetching the result (or proof) of a session
+    Result,
+    /// Cancelling a session
+    Cancel,
+    /// Connecting to the server-sent status events of a session
+    StatusEvents,
+    /// Any other endpoint, such as the public key or revocation endpoints
+    Other,
+}
+
+/// Information on a request made to the irma server
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RequestInfo {
+    /// The kind of endpoint the request was made to
+    pub endpoint: EndpointKind,
+}
+
+/// Outcome of an http exchange with the irma server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Outcome {
+    /// The server responded with the given http status
+    Status(u16),
+    /// The request timed out
+    Timeout,
+    /// No connection could be made to the server
+    ConnectFailed,
+    /// The request failed for another reason
+    Failed,
+}
+
+/// Information on the response of the irma server to a request
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ResponseInfo {
+    /// The outcome of the exchange
+    pub outcome: Outcome,
+    /// Time between sending the request and receiving the response headers, None on wasm where
+    /// it cannot be measured
+    pub elapsed: Option<Duration>,
+}
+
+/// Callback invoked after every http exchange with the irma server
+pub type ResponseObserver = Arc<dyn Fn(&RequestInfo, &ResponseInfo) + Send + Sync>;
+
+// Wrapper to allow deriving Debug on the client
+#[derive(Clone)]
+pub(crate) struct Observer(pub(crate) ResponseObserver);
+
+impl Debug for Observer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Observer").finish()
+    }
+}
+
+impl Observer {
+    pub(crate) fn observe(
+        &self,
+        endpoint: EndpointKind,
+        response: &Result<reqwest::Response, reqwest::Error>,
+        elapsed: Option<Duration>,
+    ) {
+        let outcome = match response {
+            Ok(response) => Outcome::Status(response.status().as_u16()),
+            Err(e) if e.is_timeout() => Outcome::Timeout,
+            #[cfg(not(target_arch = "wasm32"))]
+            Err(e) if e.is_connect() => Outcome::ConnectFailed,
+            Err(_) => Outcome::Failed,
+        };
+        (self.0)(
+            &RequestInfo { endpoint },
+            &ResponseInfo { outcome, elapsed },
+        );
+    }
+}
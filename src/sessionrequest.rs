@@ -1,23 +1,117 @@
-use crate::util::TranslatedString;
+use crate::{
+    util::TranslatedString, CredentialIdentifier, Error, RequestLoadError, RequestValidationError,
+    SessionType,
+};
 
 use std::{
     collections::HashMap,
+    fmt::{self, Display},
+    ops::{Deref, DerefMut},
+    path::Path,
     time::{Duration, SystemTime},
 };
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use url::Url;
 
 /// Basic structure of an IRMA disclosure request, a conjunction of disjunctions of inner conjunctions.
 /// Examples on how to use this can be found at irma.app/docs
-pub type ConDisCon = Vec<Vec<Vec<AttributeRequest>>>;
+///
+/// This dereferences to the nested vectors it used to be an alias for, so that code using it as a
+/// `Vec` keeps working. Where the nested vectors themselves are used as a `ConDisCon`, such as in
+/// `BaseRequest { disclose: vec![..], .. }`, convert them with `.into()` or [ConDisCon::from];
+/// until then, the deprecated [RawConDisCon] alias names the old type.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct ConDisCon(Vec<Vec<Vec<AttributeRequest>>>);
+
+/// The nested vectors that [ConDisCon] used to be an alias for
+#[deprecated(note = "use the ConDisCon type instead, converting nested vectors with `.into()`")]
+pub type RawConDisCon = Vec<Vec<Vec<AttributeRequest>>>;
+
+impl ConDisCon {
+    /// Create a con-dis-con without any disjunctions
+    pub fn new() -> ConDisCon {
+        ConDisCon::default()
+    }
+
+    /// Add a disjunction, of which the user discloses one of the inner conjunctions
+    pub fn push_discon(&mut self, discon: Vec<Vec<AttributeRequest>>) {
+        self.0.push(discon);
+    }
+
+    /// Iterate over the disjunctions
+    pub fn iter(&self) -> std::slice::Iter<'_, Vec<Vec<AttributeRequest>>> {
+        self.0.iter()
+    }
+
+    /// Number of disjunctions
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether there are no disjunctions
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Deref for ConDisCon {
+    type Target = Vec<Vec<Vec<AttributeRequest>>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for ConDisCon {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Vec<Vec<Vec<AttributeRequest>>>> for ConDisCon {
+    fn from(discons: Vec<Vec<Vec<AttributeRequest>>>) -> Self {
+        ConDisCon(discons)
+    }
+}
+
+impl From<ConDisCon> for Vec<Vec<Vec<AttributeRequest>>> {
+    fn from(discons: ConDisCon) -> Self {
+        discons.0
+    }
+}
+
+impl IntoIterator for ConDisCon {
+    type Item = Vec<Vec<AttributeRequest>>;
+    type IntoIter = std::vec::IntoIter<Vec<Vec<AttributeRequest>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ConDisCon {
+    type Item = &'a Vec<Vec<AttributeRequest>>;
+    type IntoIter = std::slice::Iter<'a, Vec<Vec<AttributeRequest>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
 
 fn omit_false(value: &bool) -> bool {
     !value
 }
 
+// Return and callback urls are opened by the IRMA app and called by the irma server respectively,
+// which both require an absolute http(s) url
+fn is_http_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
 /// Representation of a request for a single specific attribute
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum AttributeRequest {
     /// Request for any value of the named attribute
@@ -38,9 +132,9 @@ pub enum AttributeRequest {
 
 impl AttributeRequest {
     /// Create an attribute request for an attribute for which we require at least some value.
-    pub fn non_null(attr_type: String) -> AttributeRequest {
+    pub fn non_null(attr_type: impl Into<String>) -> AttributeRequest {
         AttributeRequest::Compound {
-            attr_type,
+            attr_type: attr_type.into(),
             value: None,
             not_null: true,
         }
@@ -49,45 +143,258 @@ impl AttributeRequest {
     /// Create an attribute request where we want a specific value for the attribute to be disclosed
     /// This is useful when using IRMA not to learn something about the user, but instead enforcing
     /// some sort of access control, such as a minimum age.
-    pub fn with_value(attr_type: String, value: String) -> AttributeRequest {
+    pub fn with_value(attr_type: impl Into<String>, value: impl Into<String>) -> AttributeRequest {
         AttributeRequest::Compound {
-            attr_type,
-            value: Some(value),
+            attr_type: attr_type.into(),
+            value: Some(value.into()),
             not_null: false,
         }
     }
 }
 
+impl From<&str> for AttributeRequest {
+    fn from(attr_type: &str) -> Self {
+        AttributeRequest::Simple(attr_type.into())
+    }
+}
+
+impl From<String> for AttributeRequest {
+    fn from(attr_type: String) -> Self {
+        AttributeRequest::Simple(attr_type)
+    }
+}
+
+/// Request for a specific value of an attribute, see [AttributeRequest::with_value]
+impl From<(&str, &str)> for AttributeRequest {
+    fn from((attr_type, value): (&str, &str)) -> Self {
+        AttributeRequest::with_value(attr_type, value)
+    }
+}
+
+/// A disjunction of a disclosure request: options of which the user discloses one, where each
+/// option is a conjunction of attributes that are disclosed together
+#[derive(Debug, Clone, Default)]
+pub struct Choice {
+    options: Vec<Vec<AttributeRequest>>,
+}
+
+impl Choice {
+    /// Create a choice without any options yet
+    pub fn new() -> Choice {
+        Choice::default()
+    }
+
+    /// Create a choice with a single option consisting of a single attribute
+    pub fn of(attr: impl Into<AttributeRequest>) -> Choice {
+        Choice::new().option([attr.into()])
+    }
+
+    /// Add an option consisting of the given attributes, all of which are disclosed when the user
+    /// chooses it
+    pub fn option<I>(mut self, attrs: I) -> Choice
+    where
+        I: IntoIterator,
+        I::Item: Into<AttributeRequest>,
+    {
+        self.options
+            .push(attrs.into_iter().map(Into::into).collect());
+        self
+    }
+}
+
+impl From<Choice> for Vec<Vec<AttributeRequest>> {
+    fn from(choice: Choice) -> Self {
+        choice.options
+    }
+}
+
+/// Request all of the given attributes: a con-dis-con with one disjunction per attribute, each
+/// having that attribute as its only option, so the user has to disclose every attribute.
+///
+/// `all_of(["a", "b"])` corresponds to `[[["a"]], [["b"]]]`. Note that attributes from different
+/// disjunctions may come from different credentials; for attributes that have to come from the same
+/// credential, use a single option with [one_of_sets] instead.
+pub fn all_of<I>(attrs: I) -> ConDisCon
+where
+    I: IntoIterator,
+    I::Item: Into<AttributeRequest>,
+{
+    attrs
+        .into_iter()
+        .map(|attr| vec![vec![attr.into()]])
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// Request any one of the given attributes: a single disjunction with one option per attribute,
+/// so the user discloses exactly one of them, of their choice.
+///
+/// `any_of(["a", "b"])` corresponds to the disjunction `[["a"], ["b"]]`, to be added to a request
+/// with `add_discon`.
+pub fn any_of<I>(attrs: I) -> Vec<Vec<AttributeRequest>>
+where
+    I: IntoIterator,
+    I::Item: Into<AttributeRequest>,
+{
+    attrs.into_iter().map(|attr| vec![attr.into()]).collect()
+}
+
+/// Request one of the given sets of attributes: a single disjunction with one option per set, so
+/// the user discloses all attributes of exactly one set, of their choice.
+///
+/// `one_of_sets([["a", "b"], ["c", "d"]])` corresponds to the disjunction
+/// `[["a", "b"], ["c", "d"]]`, to be added to a request with `add_discon`. The attributes within a
+/// set that belong to the same credential type are disclosed from the same credential.
+pub fn one_of_sets<I, S>(sets: I) -> Vec<Vec<AttributeRequest>>
+where
+    I: IntoIterator<Item = S>,
+    S: IntoIterator,
+    S::Item: Into<AttributeRequest>,
+{
+    sets.into_iter()
+        .map(|set| set.into_iter().map(Into::into).collect())
+        .collect()
+}
+
 /// Description of an IRMA credential to be issued.
 /// The issuing IRMA server requires the private key of the issuer to be present to be able to issue a credential.
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Credential {
     /// Identifier of the credential to be issued
-    pub credential: String,
+    pub credential: CredentialIdentifier,
     /// Unix timestamp of until when the credential is valid. This is rounded down by the server to the nearest week.
     /// When not present, the server will default the credential to be valid for 6 months
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub validity: Option<u64>,
     /// Values for the attributes in the credential
-    pub attributes: HashMap<String, String>,
+    #[serde(serialize_with = "ser_attributes")]
+    pub attributes: HashMap<String, AttributeValue>,
+    /// Key with which the issuer can later revoke the credential, for revocable credential types
+    #[serde(
+        rename = "revocationKey",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub revocation_key: Option<String>,
+}
+
+// The irma server rounds the validity of issued credentials down to a multiple of a week
+const VALIDITY_GRANULARITY: u64 = 60 * 60 * 24 * 7;
+
+impl Credential {
+    /// The validity as it will be stored in the issued credential, after the rounding down by the
+    /// irma server. None when the validity is left to the server.
+    pub fn effective_validity(&self) -> Option<SystemTime> {
+        self.validity.map(|validity| {
+            SystemTime::UNIX_EPOCH
+                + Duration::from_secs(validity / VALIDITY_GRANULARITY * VALIDITY_GRANULARITY)
+        })
+    }
+}
+
+/// Value of an attribute in a credential to be issued
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "Option<String>", into = "Option<String>")]
+pub enum AttributeValue {
+    /// Value chosen by the issuer
+    Value(String),
+    /// Explicitly empty value, for optional attributes in the credential type. This is sent as
+    /// the empty string, which the irma server accepts for optional attributes only.
+    Empty,
+    /// No value, for random-blind attributes, of which the value is generated randomly during
+    /// issuance instead of chosen by the issuer. These are left out of the issuance request.
+    RandomBlind,
+}
+
+impl From<Option<String>> for AttributeValue {
+    fn from(value: Option<String>) -> Self {
+        match value {
+            Some(value) if value.is_empty() => AttributeValue::Empty,
+            Some(value) => AttributeValue::Value(value),
+            None => AttributeValue::RandomBlind,
+        }
+    }
+}
+
+impl From<AttributeValue> for Option<String> {
+    fn from(value: AttributeValue) -> Self {
+        match value {
+            AttributeValue::Value(value) => Some(value),
+            AttributeValue::Empty => Some(String::new()),
+            AttributeValue::RandomBlind => None,
+        }
+    }
+}
+
+// The empty string is an explicitly empty value, as that is how it comes back after serialization
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        Some(value).into()
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        String::from(value).into()
+    }
+}
+
+// The irma server expects random-blind attributes to be absent from the issuance request
+fn ser_attributes<S: serde::Serializer>(
+    attributes: &HashMap<String, AttributeValue>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(attributes.iter().filter_map(|(key, value)| match value {
+        AttributeValue::Value(value) => Some((key, value.as_str())),
+        AttributeValue::Empty => Some((key, "")),
+        AttributeValue::RandomBlind => None,
+    }))
 }
 
 /// Builder for an IRMA credential
+#[derive(Debug, Clone)]
 pub struct CredentialBuilder {
     cred: Credential,
+    // First problem with the credential, reported when building
+    error: Option<RequestValidationError>,
 }
 
 impl CredentialBuilder {
-    /// Create a builder for a credential
+    /// Create a builder for a credential of the given type. An invalid identifier of the
+    /// credential type is reported when building, see [CredentialBuilder::try_build].
     pub fn new(credential: String) -> CredentialBuilder {
+        match CredentialIdentifier::try_from(credential.clone()) {
+            Ok(credential) => CredentialBuilder::from_identifier(credential),
+            Err(e) => CredentialBuilder {
+                error: Some(RequestValidationError::InvalidCredentialType(e)),
+                ..CredentialBuilder::from_identifier(CredentialIdentifier::unchecked(credential))
+            },
+        }
+    }
+
+    /// Create a builder for a credential of the given, already validated, type
+    pub fn from_identifier(credential: CredentialIdentifier) -> CredentialBuilder {
         CredentialBuilder {
             cred: Credential {
                 credential,
                 validity: None,
                 attributes: HashMap::new(),
+                revocation_key: None,
             },
+            error: None,
+        }
+    }
+
+    /// Set until when the credential is valid. The irma server rounds this down to a multiple of a
+    /// week, see [Credential::effective_validity].
+    pub fn validity_until(mut self, until: SystemTime) -> Self {
+        match until.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(timestamp) => self.cred.validity = Some(timestamp.as_secs()),
+            Err(_) => self.fail(RequestValidationError::ValidityBeforeEpoch(
+                self.cred.credential.to_string(),
+            )),
         }
+        self
     }
 
     /// Set the validity period
@@ -102,24 +409,122 @@ impl CredentialBuilder {
         self
     }
 
-    /// Add an indivial attribute
-    pub fn attribute(mut self, key: String, value: String) -> Self {
+    fn fail(&mut self, error: RequestValidationError) {
+        self.error.get_or_insert(error);
+    }
+
+    fn insert(&mut self, key: String, value: AttributeValue) {
+        if self.cred.attributes.contains_key(&key) {
+            self.fail(RequestValidationError::DuplicateAttribute {
+                credential: self.cred.credential.to_string(),
+                attribute: key.clone(),
+            });
+        }
+        let value = match value {
+            AttributeValue::Value(value) => value.into(),
+            value => value,
+        };
         self.cred.attributes.insert(key, value);
+    }
+
+    /// Add an indivial attribute. An empty value is stored as [AttributeValue::Empty].
+    pub fn attribute(mut self, key: String, value: String) -> Self {
+        self.insert(key, AttributeValue::Value(value));
+        self
+    }
+
+    /// Add multiple attributes, for example from a `HashMap` or a `Vec` of pairs
+    pub fn attributes<K, V>(mut self, attributes: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        for (key, value) in attributes {
+            self.insert(key.into(), AttributeValue::Value(value.into()));
+        }
+        self
+    }
+
+    /// Add the fields of the given struct (or map) as attributes, using the field names as
+    /// serialized by serde. Strings and numbers are used as is, booleans become `yes` or `no` as
+    /// is customary in IRMA schemes, and fields without value (such as `None`) are skipped.
+    /// Nested structs, maps and sequences cannot be represented as attributes and result in an
+    /// error.
+    pub fn attributes_from<T: Serialize>(mut self, value: &T) -> Result<Self, Error> {
+        let fields = match serde_json::to_value(value) {
+            Ok(serde_json::Value::Object(fields)) => fields,
+            Ok(_) => {
+                return Err(Error::InvalidAttributes(
+                    "expected a struct or map of attributes".into(),
+                ))
+            }
+            Err(e) => return Err(Error::InvalidAttributes(e.to_string())),
+        };
+        for (key, value) in fields {
+            let value = match value {
+                serde_json::Value::Null => continue,
+                serde_json::Value::String(value) => value,
+                serde_json::Value::Number(value) => value.to_string(),
+                serde_json::Value::Bool(value) => if value { "yes" } else { "no" }.into(),
+                serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                    return Err(Error::NestedAttributeValue(key))
+                }
+            };
+            self.insert(key, AttributeValue::Value(value));
+        }
+        Ok(self)
+    }
+
+    /// Add an optional attribute without value
+    pub fn empty_attribute(mut self, key: String) -> Self {
+        self.insert(key, AttributeValue::Empty);
+        self
+    }
+
+    /// Add a random-blind attribute, of which the value is generated randomly during issuance
+    pub fn random_blind_attribute(mut self, key: String) -> Self {
+        self.insert(key, AttributeValue::RandomBlind);
+        self
+    }
+
+    /// Set the key with which the credential can be revoked later on (see
+    /// [crate::IrmaClient::revoke]). The key must be unique among the issued credentials of its
+    /// type.
+    pub fn revocation_key(mut self, key: String) -> Self {
+        self.cred.revocation_key = Some(key);
         self
     }
 
     /// Create the credential
+    ///
+    /// # Panics
+    /// Panics when the credential is invalid, see [CredentialBuilder::try_build]
     pub fn build(self) -> Credential {
-        self.cred
+        self.try_build()
+            .unwrap_or_else(|e| panic!("Invalid credential: {}", e))
+    }
+
+    /// Create the credential, failing when the identifier of its type is invalid, an attribute was
+    /// set more than once or the validity lies before the Unix epoch
+    pub fn try_build(self) -> Result<Credential, RequestValidationError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.cred),
+        }
     }
 }
 
+// Whether the option at the given index is the empty option that makes a disjunction optional,
+// which is only allowed as the last of multiple options
+fn is_optional_marker(discon: &[Vec<AttributeRequest>], conjunction: usize) -> bool {
+    conjunction > 0 && conjunction + 1 == discon.len() && discon[conjunction].is_empty()
+}
+
 /// Information common between all types of requests
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct BaseRequest {
     /// Con-dis-con of attributes to be disclosed
-    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    #[serde(skip_serializing_if = "ConDisCon::is_empty", default)]
     pub disclose: ConDisCon,
     /// For mobile sessions, URL to redirect user to after completion of the session.
     #[serde(rename = "clientReturnUrl", skip_serializing_if = "Option::is_none")]
@@ -138,12 +543,95 @@ pub struct BaseRequest {
         deserialize_with = "crate::util::de_int_key"
     )]
     pub labels: HashMap<usize, TranslatedString>,
+    /// Credential types for which the IRMA app has to include proofs of non-revocation
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub revocation: Vec<String>,
+    /// Credential types of which attributes are accepted even if the credential has expired
+    #[serde(
+        rename = "skipExpiryCheck",
+        skip_serializing_if = "Vec::is_empty",
+        default
+    )]
+    pub skip_expiry_check: Vec<String>,
+}
+
+impl BaseRequest {
+    // Problems for which the irma server rejects the request, in the order of the request.
+    // Disjunctions are only required when attributes are the point of the request, i.e. for
+    // disclosure and signature requests. The attributes within conjunctions are only checked when
+    // asked for, as the builders leave those to the server.
+    fn issues(
+        &self,
+        require_disjunctions: bool,
+        check_attributes: bool,
+    ) -> Vec<RequestValidationError> {
+        let mut issues = vec![];
+        if require_disjunctions && self.disclose.is_empty() {
+            issues.push(RequestValidationError::NoDisjunctions);
+        }
+
+        for (disjunction, discon) in self.disclose.iter().enumerate() {
+            if discon.is_empty() {
+                issues.push(RequestValidationError::EmptyDisjunction { disjunction });
+            }
+            for (conjunction, con) in discon.iter().enumerate() {
+                if con.is_empty() && !is_optional_marker(discon, conjunction) {
+                    issues.push(RequestValidationError::EmptyInnerConjunction {
+                        disjunction,
+                        conjunction,
+                    });
+                }
+                if !check_attributes {
+                    continue;
+                }
+                let mut seen = vec![];
+                for attr in con {
+                    let (attr_type, pinned) = match attr {
+                        AttributeRequest::Simple(attr_type) => (attr_type, false),
+                        AttributeRequest::Compound {
+                            attr_type,
+                            value,
+                            not_null,
+                        } => (attr_type, value.is_some() && *not_null),
+                    };
+                    if pinned {
+                        issues.push(RequestValidationError::ValueAndNotNull {
+                            disjunction,
+                            conjunction,
+                            attribute: attr_type.clone(),
+                        });
+                    }
+                    if seen.contains(&attr_type) {
+                        issues.push(RequestValidationError::RepeatedAttribute {
+                            disjunction,
+                            conjunction,
+                            attribute: attr_type.clone(),
+                        });
+                    } else {
+                        seen.push(attr_type);
+                    }
+                }
+            }
+        }
+
+        let mut labels: Vec<_> = self.labels.keys().copied().collect();
+        labels.sort_unstable();
+        for index in labels
+            .into_iter()
+            .filter(|&index| index >= self.disclose.len())
+        {
+            issues.push(RequestValidationError::LabelOutOfRange {
+                index,
+                disjunctions: self.disclose.len(),
+            });
+        }
+        issues
+    }
 }
 
 /// IRMA session requests
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[cfg_attr(test, derive(PartialEq))]
-#[serde(tag = "@context")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "@context", remote = "Self")]
 pub enum IrmaRequest {
     /// Request for the disclosure of some set of attributes
     #[serde(rename = "https://irma.app/ld/request/disclosure/v2")]
@@ -167,32 +655,306 @@ pub enum IrmaRequest {
     },
 }
 
+// Values of the @context field of the supported types of requests
+const REQUEST_CONTEXTS: [&str; 3] = [
+    "https://irma.app/ld/request/disclosure/v2",
+    "https://irma.app/ld/request/signature/v2",
+    "https://irma.app/ld/request/issuance/v2",
+];
+
+impl Serialize for IrmaRequest {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        IrmaRequest::serialize(self, serializer)
+    }
+}
+
+// The supported @context of the request in its JSON form, or a description of what is wrong with it
+fn request_context(value: &serde_json::Value) -> Result<&str, String> {
+    match value.get("@context") {
+        Some(serde_json::Value::String(context))
+            if REQUEST_CONTEXTS.contains(&context.as_str()) =>
+        {
+            Ok(context)
+        }
+        Some(serde_json::Value::String(context)) => Err(format!(
+            "unknown @context {:?} of session request, supported are: {}",
+            context,
+            REQUEST_CONTEXTS.join(", ")
+        )),
+        Some(_) => Err("@context of session request is not a string".into()),
+        None => Err(format!(
+            "session request has no @context field, expected one of: {}",
+            REQUEST_CONTEXTS.join(", ")
+        )),
+    }
+}
+
+// The derived implementation reports an unknown @context only as an unknown variant, so the
+// context is checked first to give a clearer error
+impl<'de> Deserialize<'de> for IrmaRequest {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        let value = serde_json::Value::deserialize(deserializer)?;
+        request_context(&value).map_err(D::Error::custom)?;
+        IrmaRequest::deserialize(value).map_err(D::Error::custom)
+    }
+}
+
+// Deserialize the field with the given key from a request loaded from JSON, or the base request
+// when no key is given, keeping track of where in the JSON deserialization fails
+fn load_part<T: DeserializeOwned>(
+    request: &serde_json::Value,
+    key: Option<&str>,
+) -> Result<T, RequestLoadError> {
+    let (mut pointer, part) = match key {
+        Some(key) => match request.get(key) {
+            Some(part) => (format!("/{}", key), part.clone()),
+            None => {
+                return Err(RequestLoadError::Invalid {
+                    pointer: String::new(),
+                    message: format!("missing field `{}`", key),
+                })
+            }
+        },
+        None => (String::new(), request.clone()),
+    };
+    serde_path_to_error::deserialize(part).map_err(|e| {
+        for segment in e.path().iter() {
+            pointer.push('/');
+            match segment {
+                serde_path_to_error::Segment::Seq { index } => pointer.push_str(&index.to_string()),
+                serde_path_to_error::Segment::Map { key }
+                | serde_path_to_error::Segment::Enum { variant: key } => {
+                    pointer.push_str(&key.replace('~', "~0").replace('/', "~1"))
+                }
+                serde_path_to_error::Segment::Unknown => pointer.push('?'),
+            }
+        }
+        RequestLoadError::Invalid {
+            pointer,
+            message: e.into_inner().to_string(),
+        }
+    })
+}
+
+impl IrmaRequest {
+    /// Create a request for the disclosure of the given attributes, validated as by
+    /// [DisclosureRequestBuilder::try_build]. Use the builder to set return URLs and labels.
+    pub fn disclosure(
+        condiscon: impl Into<ConDisCon>,
+    ) -> Result<IrmaRequest, RequestValidationError> {
+        DisclosureRequestBuilder::new()
+            .add_discons(condiscon)
+            .try_build()
+    }
+
+    /// Create a request for signing the given message with the given attributes, validated as by
+    /// [SignatureRequestBuilder::try_build]. Use the builder to set return URLs and labels.
+    pub fn signature(
+        message: impl Into<String>,
+        condiscon: impl Into<ConDisCon>,
+    ) -> Result<IrmaRequest, RequestValidationError> {
+        SignatureRequestBuilder::new(message.into())
+            .add_discons(condiscon)
+            .try_build()
+    }
+
+    /// Create a request for the issuance of the given credentials, validated as by
+    /// [IssuanceRequestBuilder::try_build]. Use the builder to also request attributes.
+    pub fn issuance(credentials: Vec<Credential>) -> Result<IrmaRequest, RequestValidationError> {
+        IssuanceRequestBuilder::new()
+            .add_credentials(credentials)
+            .try_build()
+    }
+
+    /// The type of session started with this request
+    pub fn session_type(&self) -> SessionType {
+        match self {
+            IrmaRequest::Disclosure { .. } => SessionType::Disclosing,
+            IrmaRequest::Signature { .. } => SessionType::Signing,
+            IrmaRequest::Issuance { .. } => SessionType::Issuing,
+        }
+    }
+
+    /// The information common between all types of requests
+    pub fn base(&self) -> &BaseRequest {
+        match self {
+            IrmaRequest::Disclosure { base }
+            | IrmaRequest::Signature { base, .. }
+            | IrmaRequest::Issuance { base, .. } => base,
+        }
+    }
+
+    /// The attributes to be disclosed
+    pub fn disclose(&self) -> &ConDisCon {
+        &self.base().disclose
+    }
+
+    /// The credentials to be issued, for issuance requests
+    pub fn credentials(&self) -> Option<&[Credential]> {
+        match self {
+            IrmaRequest::Issuance { credentials, .. } => Some(credentials),
+            _ => None,
+        }
+    }
+
+    /// The message to be signed, for signature requests
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            IrmaRequest::Signature { message, .. } => Some(message),
+            _ => None,
+        }
+    }
+
+    /// The URL the user is redirected to after completing the session on mobile, if any
+    pub fn return_url(&self) -> Option<&str> {
+        self.base().return_url.as_deref()
+    }
+
+    /// Load a request from its JSON form, for example from a configuration file, and check it with
+    /// [IrmaRequest::validate]. When the JSON does not describe a request, the error points to the
+    /// value at which the problem was found.
+    pub fn from_json(json: &str) -> Result<IrmaRequest, RequestLoadError> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(RequestLoadError::Syntax)?;
+        if !value.is_object() {
+            return Err(RequestLoadError::Invalid {
+                pointer: String::new(),
+                message: "session request is not a JSON object".into(),
+            });
+        }
+        let context = request_context(&value).map_err(|message| RequestLoadError::Invalid {
+            pointer: match value.get("@context") {
+                Some(_) => "/@context".into(),
+                None => String::new(),
+            },
+            message,
+        })?;
+
+        let base = load_part(&value, None)?;
+        let request = match context {
+            "https://irma.app/ld/request/signature/v2" => IrmaRequest::Signature {
+                message: load_part(&value, Some("message"))?,
+                base,
+            },
+            "https://irma.app/ld/request/issuance/v2" => IrmaRequest::Issuance {
+                credentials: load_part(&value, Some("credentials"))?,
+                base,
+            },
+            _ => IrmaRequest::Disclosure { base },
+        };
+        request
+            .validate()
+            .map_err(RequestLoadError::InvalidRequest)?;
+        Ok(request)
+    }
+
+    /// Load a request from a file containing its JSON form, see [IrmaRequest::from_json]
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<IrmaRequest, RequestLoadError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|source| RequestLoadError::Io {
+            path: path.into(),
+            source,
+        })?;
+        IrmaRequest::from_json(&json)
+    }
+
+    /// Check the structure of the request, reporting all problems for which the irma server would
+    /// reject it. Besides the problems the request builders reject, this finds attributes that are
+    /// requested more than once within a conjunction, and attributes that require both a specific
+    /// and a non-empty value.
+    pub fn validate(&self) -> Result<(), Vec<RequestValidationError>> {
+        let mut issues = vec![];
+        let require_disjunctions = match self {
+            IrmaRequest::Issuance { credentials, .. } => {
+                if credentials.is_empty() {
+                    issues.push(RequestValidationError::NoCredentials);
+                }
+                false
+            }
+            IrmaRequest::Disclosure { .. } | IrmaRequest::Signature { .. } => true,
+        };
+        issues.extend(self.base().issues(require_disjunctions, true));
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+
+    /// The request in the compact JSON form sent to the irma server
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string(self).map_err(Error::RequestSerialization)
+    }
+
+    /// The request as indented JSON, as also produced by its [Display] implementation
+    pub fn to_json_pretty(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).map_err(Error::RequestSerialization)
+    }
+}
+
+impl Display for IrmaRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_json_pretty() {
+            Ok(json) => f.write_str(&json),
+            Err(_) => write!(f, "{:?}", self),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 struct BaseRequestBuilder {
     base: BaseRequest,
+    // First problem with the request, reported when building
+    error: Option<RequestValidationError>,
 }
 
 impl BaseRequestBuilder {
     fn new() -> BaseRequestBuilder {
         BaseRequestBuilder {
             base: BaseRequest {
-                disclose: vec![],
+                disclose: ConDisCon::new(),
                 return_url: None,
                 augment_return: false,
                 labels: HashMap::new(),
+                revocation: vec![],
+                skip_expiry_check: vec![],
             },
+            error: None,
         }
     }
 
-    fn build(self) -> BaseRequest {
-        self.base
+    // Disjunctions are only required when attributes are the point of the request, i.e. for
+    // disclosure and signature requests
+    fn try_build(self, require_disjunctions: bool) -> Result<BaseRequest, RequestValidationError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        match self
+            .base
+            .issues(require_disjunctions, false)
+            .into_iter()
+            .next()
+        {
+            Some(error) => Err(error),
+            None => Ok(self.base),
+        }
     }
 
-    fn add_discons(&mut self, mut discons: ConDisCon) {
-        self.base.disclose.append(&mut discons);
+    fn add_discons(&mut self, discons: ConDisCon) {
+        discons
+            .into_iter()
+            .for_each(|discon| self.base.disclose.push_discon(discon));
     }
 
     fn add_discon(&mut self, discon: Vec<Vec<AttributeRequest>>) {
-        self.base.disclose.push(discon);
+        self.base.disclose.push_discon(discon);
+    }
+
+    fn add_optional_discon(&mut self, mut discon: Vec<Vec<AttributeRequest>>) {
+        discon.push(vec![]);
+        self.base.disclose.push_discon(discon);
     }
 
     fn add_discon_with_label(
@@ -201,19 +963,97 @@ impl BaseRequestBuilder {
         label: TranslatedString,
     ) {
         let index = self.base.disclose.len();
-        self.base.disclose.push(discon);
+        self.base.disclose.push_discon(discon);
         self.base.labels.insert(index, label);
     }
 
+    fn set_label(&mut self, index: usize, label: TranslatedString) {
+        self.base.labels.insert(index, label);
+    }
+
+    fn clear_label(&mut self, index: usize) {
+        self.base.labels.remove(&index);
+    }
+
+    // Labels of later disjunctions move along with their disjunction
+    fn remove_discon(&mut self, index: usize) {
+        if index >= self.base.disclose.len() {
+            return;
+        }
+        self.base.disclose.0.remove(index);
+        self.base.labels = std::mem::take(&mut self.base.labels)
+            .into_iter()
+            .filter(|(i, _)| *i != index)
+            .map(|(i, label)| {
+                if i > index {
+                    (i - 1, label)
+                } else {
+                    (i, label)
+                }
+            })
+            .collect();
+    }
+
+    // Also forgets earlier problems with the return url
+    fn clear_return_url(&mut self) {
+        self.base.return_url = None;
+        self.base.augment_return = false;
+        if matches!(
+            self.error,
+            Some(
+                RequestValidationError::InvalidReturnUrl(_)
+                    | RequestValidationError::ReturnUrlSetTwice { .. }
+            )
+        ) {
+            self.error = None;
+        }
+    }
+
+    fn fail(&mut self, error: RequestValidationError) {
+        self.error.get_or_insert(error);
+    }
+
+    // Problems with the return url are reported when building, keeping the first valid one until
+    // then
+    fn set_return_url(&mut self, return_url: String, augment: bool, checked: bool) {
+        if checked && !is_http_url(&return_url) {
+            self.fail(RequestValidationError::InvalidReturnUrl(return_url));
+            return;
+        }
+        match &self.base.return_url {
+            Some(first) => self.fail(RequestValidationError::ReturnUrlSetTwice {
+                first: first.clone(),
+                second: return_url,
+            }),
+            None => {
+                self.base.return_url = Some(return_url);
+                self.base.augment_return = augment;
+            }
+        }
+    }
+
     fn return_url(&mut self, return_url: String) {
-        debug_assert!(self.base.return_url == None);
-        self.base.return_url = Some(return_url);
+        self.set_return_url(return_url, false, true);
+    }
+
+    fn return_url_unchecked(&mut self, return_url: String) {
+        self.set_return_url(return_url, false, false);
     }
 
     fn augmented_return_url(&mut self, return_url: String) {
-        debug_assert!(self.base.return_url == None);
-        self.base.return_url = Some(return_url);
-        self.base.augment_return = true;
+        self.set_return_url(return_url, true, true);
+    }
+
+    fn require_nonrevocation(&mut self, credential_type: String) {
+        if !self.base.revocation.contains(&credential_type) {
+            self.base.revocation.push(credential_type);
+        }
+    }
+
+    fn skip_expiry_check(&mut self, credential_type: String) {
+        if !self.base.skip_expiry_check.contains(&credential_type) {
+            self.base.skip_expiry_check.push(credential_type);
+        }
     }
 }
 
@@ -223,8 +1063,182 @@ impl Default for BaseRequestBuilder {
     }
 }
 
+// Methods of the request builders that only configure the base request, defined once for the
+// builders of all session types
+macro_rules! base_builder_methods {
+    ($builder:ident) => {
+        impl $builder {
+            /// Add an additional disjunction to the request
+            pub fn add_discon(mut self, discon: Vec<Vec<AttributeRequest>>) -> $builder {
+                self.base.add_discon(discon);
+                self
+            }
+
+            /// Add multiple additional disjunctions to the request
+            pub fn add_discons(mut self, discons: impl Into<ConDisCon>) -> $builder {
+                self.base.add_discons(discons.into());
+                self
+            }
+
+            /// Add an additional disjunction to the request that the user may also leave empty, by
+            /// appending an empty option after the given options. In the session result, the
+            /// attributes disclosed for it are empty when the user chose not to disclose anything.
+            pub fn add_optional_discon(
+                mut self,
+                choices: impl Into<Vec<Vec<AttributeRequest>>>,
+            ) -> $builder {
+                self.base.add_optional_discon(choices.into());
+                self
+            }
+
+            /// Add an additional labeled disjunction to the request
+            pub fn add_discon_with_label(
+                mut self,
+                discon: Vec<Vec<AttributeRequest>>,
+                label: impl Into<TranslatedString>,
+            ) -> $builder {
+                self.base.add_discon_with_label(discon, label.into());
+                self
+            }
+
+            /// Set or replace the label of the disjunction with the given index, counting from 0 in
+            /// the order in which the disjunctions were added
+            pub fn set_label(
+                mut self,
+                index: usize,
+                label: impl Into<TranslatedString>,
+            ) -> $builder {
+                self.base.set_label(index, label.into());
+                self
+            }
+
+            /// Remove the label of the disjunction with the given index, if any
+            pub fn clear_label(mut self, index: usize) -> $builder {
+                self.base.clear_label(index);
+                self
+            }
+
+            /// Remove the disjunction with the given index, together with its label. Later
+            /// disjunctions and their labels move up by one. Does nothing when there is no such
+            /// disjunction.
+            pub fn remove_discon(mut self, index: usize) -> $builder {
+                self.base.remove_discon(index);
+                self
+            }
+
+            /// Number of disjunctions added so far
+            pub fn discon_count(&self) -> usize {
+                self.base.base.disclose.len()
+            }
+
+            /// The labels of the disjunctions added so far, by index
+            pub fn labels(&self) -> &HashMap<usize, TranslatedString> {
+                &self.base.base.labels
+            }
+
+            /// Add a disjunction to the request from the options of the given choice
+            pub fn add_choice(mut self, choice: Choice) -> $builder {
+                self.base.add_discon(choice.into());
+                self
+            }
+
+            /// Add a disjunction to the request asking for exactly the given attribute
+            pub fn add_attribute(mut self, attr: impl Into<AttributeRequest>) -> $builder {
+                self.base.add_discon(vec![vec![attr.into()]]);
+                self
+            }
+
+            /// Add a disjunction to the request for each of the given attributes, asking for
+            /// exactly that attribute
+            pub fn add_attributes<I>(mut self, attrs: I) -> $builder
+            where
+                I: IntoIterator,
+                I::Item: Into<AttributeRequest>,
+            {
+                for attr in attrs {
+                    self.base.add_discon(vec![vec![attr.into()]]);
+                }
+                self
+            }
+
+            /// Add a labeled disjunction to the request asking for exactly the given attribute
+            pub fn add_attribute_with_label(
+                mut self,
+                attr: impl Into<AttributeRequest>,
+                label: impl Into<TranslatedString>,
+            ) -> $builder {
+                self.base
+                    .add_discon_with_label(vec![vec![attr.into()]], label.into());
+                self
+            }
+
+            /// Set a return URL on the request, which must be an absolute http or https URL. A
+            /// request has only one return URL, so setting another one (also with
+            /// `augmented_return_url`) makes building the request fail.
+            pub fn return_url(mut self, return_url: String) -> $builder {
+                self.base.return_url(return_url);
+                self
+            }
+
+            /// Set a return URL on the request without checking it, for example for URLs with the
+            /// custom scheme of an app
+            pub fn return_url_unchecked(mut self, return_url: String) -> $builder {
+                self.base.return_url_unchecked(return_url);
+                self
+            }
+
+            /// Remove the return URL, if any, so that another one can be set
+            pub fn clear_return_url(mut self) -> $builder {
+                self.base.clear_return_url();
+                self
+            }
+
+            /// Whether a return URL is set
+            pub fn has_return_url(&self) -> bool {
+                self.base.base.return_url.is_some()
+            }
+
+            /// Set an augmented return url on the request, which must be an absolute http or https
+            /// URL. A request has only one return URL, so setting another one (also with
+            /// `return_url`) makes building the request fail.
+            pub fn augmented_return_url(mut self, return_url: String) -> $builder {
+                self.base.augmented_return_url(return_url);
+                self
+            }
+        }
+    };
+}
+
+// Methods for the options on the disclosed attributes, which only disclosure and signature
+// requests support
+macro_rules! disclosure_builder_methods {
+    ($builder:ident) => {
+        impl $builder {
+            /// Require a proof of non-revocation for the disclosed attributes of the given
+            /// credential type (which must be revocable)
+            pub fn require_nonrevocation(mut self, credential_type: String) -> $builder {
+                self.base.require_nonrevocation(credential_type);
+                self
+            }
+
+            /// Accept attributes from expired credentials of the given credential type.
+            ///
+            /// **Security:** this weakens the guarantees of the session, as the issuer no longer
+            /// vouches for attributes of expired credentials. Only use it for deliberate flows such
+            /// as grace periods. Without it, disclosing attributes of an expired credential results
+            /// in a [ProofStatus::Expired](crate::ProofStatus::Expired) in the session result; with
+            /// it, the proof can be valid, so check the expiry of such attributes yourself where it
+            /// matters.
+            pub fn skip_expiry_check(mut self, credential_type: String) -> $builder {
+                self.base.skip_expiry_check(credential_type);
+                self
+            }
+        }
+    };
+}
+
 /// Build a disclosure request
-#[derive(Default)]
+#[derive(Debug, Clone, Default)]
 pub struct DisclosureRequestBuilder {
     base: BaseRequestBuilder,
 }
@@ -236,49 +1250,28 @@ impl DisclosureRequestBuilder {
     }
 
     /// Construct the actual request based on the given information
+    ///
+    /// # Panics
+    /// Panics when the request is invalid, see [DisclosureRequestBuilder::try_build]
     pub fn build(self) -> IrmaRequest {
-        debug_assert!(!self.base.base.disclose.is_empty());
-        IrmaRequest::Disclosure {
-            base: self.base.build(),
-        }
-    }
-
-    /// Add an additional disjunction to the request
-    pub fn add_discon(mut self, discon: Vec<Vec<AttributeRequest>>) -> DisclosureRequestBuilder {
-        self.base.add_discon(discon);
-        self
-    }
-
-    /// Add multiple additional disjunctions to the request
-    pub fn add_discons(mut self, discons: ConDisCon) -> DisclosureRequestBuilder {
-        self.base.add_discons(discons);
-        self
-    }
-
-    /// Add an additional labeled disjunction to the request
-    pub fn add_discon_with_label(
-        mut self,
-        discon: Vec<Vec<AttributeRequest>>,
-        label: TranslatedString,
-    ) -> DisclosureRequestBuilder {
-        self.base.add_discon_with_label(discon, label);
-        self
+        self.try_build()
+            .unwrap_or_else(|e| panic!("Invalid disclosure request: {}", e))
     }
 
-    /// Set a return URL on the request
-    pub fn return_url(mut self, return_url: String) -> DisclosureRequestBuilder {
-        self.base.return_url(return_url);
-        self
-    }
-
-    /// Set an augmented return url on the request
-    pub fn augmented_return_url(mut self, return_url: String) -> DisclosureRequestBuilder {
-        self.base.augmented_return_url(return_url);
-        self
+    /// Construct the actual request based on the given information, failing when it asks for no
+    /// attributes or contains empty disjunctions or conjunctions
+    pub fn try_build(self) -> Result<IrmaRequest, RequestValidationError> {
+        Ok(IrmaRequest::Disclosure {
+            base: self.base.try_build(true)?,
+        })
     }
 }
 
+base_builder_methods!(DisclosureRequestBuilder);
+disclosure_builder_methods!(DisclosureRequestBuilder);
+
 /// Build a signature request
+#[derive(Debug, Clone)]
 pub struct SignatureRequestBuilder {
     message: String,
     base: BaseRequestBuilder,
@@ -293,52 +1286,38 @@ impl SignatureRequestBuilder {
         }
     }
 
+    /// Construct a new builder for a request to sign a structured message, serialized to JSON in
+    /// the canonical form of [canonical_json](crate::canonical_json). Use
+    /// [SessionResult::signs_json](crate::SessionResult::signs_json) to check a signed message
+    /// against a candidate document.
+    pub fn new_json<T: Serialize + ?Sized>(value: &T) -> Result<SignatureRequestBuilder, Error> {
+        Ok(SignatureRequestBuilder::new(crate::canonical_json(value)?))
+    }
+
     /// Construct the actual request based on the given information
+    ///
+    /// # Panics
+    /// Panics when the request is invalid, see [SignatureRequestBuilder::try_build]
     pub fn build(self) -> IrmaRequest {
-        debug_assert!(!self.base.base.disclose.is_empty());
-        IrmaRequest::Signature {
-            message: self.message,
-            base: self.base.build(),
-        }
-    }
-
-    /// Add an additional disjunction to the request
-    pub fn add_discon(mut self, discon: Vec<Vec<AttributeRequest>>) -> SignatureRequestBuilder {
-        self.base.add_discon(discon);
-        self
-    }
-
-    /// Add multiple additional disjunctions to the request
-    pub fn add_discons(mut self, discons: ConDisCon) -> SignatureRequestBuilder {
-        self.base.add_discons(discons);
-        self
-    }
-
-    /// Add an additional labeled disjunction to the request
-    pub fn add_discon_with_label(
-        mut self,
-        discon: Vec<Vec<AttributeRequest>>,
-        label: TranslatedString,
-    ) -> SignatureRequestBuilder {
-        self.base.add_discon_with_label(discon, label);
-        self
-    }
-
-    /// Set a return URL on the request
-    pub fn return_url(mut self, return_url: String) -> SignatureRequestBuilder {
-        self.base.return_url(return_url);
-        self
+        self.try_build()
+            .unwrap_or_else(|e| panic!("Invalid signature request: {}", e))
     }
 
-    /// Set an augmented return url on the request
-    pub fn augmented_return_url(mut self, return_url: String) -> SignatureRequestBuilder {
-        self.base.augmented_return_url(return_url);
-        self
+    /// Construct the actual request based on the given information, failing when it asks for no
+    /// attributes or contains empty disjunctions or conjunctions
+    pub fn try_build(self) -> Result<IrmaRequest, RequestValidationError> {
+        Ok(IrmaRequest::Signature {
+            message: self.message,
+            base: self.base.try_build(true)?,
+        })
     }
 }
 
+base_builder_methods!(SignatureRequestBuilder);
+disclosure_builder_methods!(SignatureRequestBuilder);
+
 /// Build a request to issue one or more credentials
-#[derive(Default)]
+#[derive(Debug, Clone, Default)]
 pub struct IssuanceRequestBuilder {
     credentials: Vec<Credential>,
     base: BaseRequestBuilder,
@@ -354,12 +1333,25 @@ impl IssuanceRequestBuilder {
     }
 
     /// Construct the actual request based on the given information
+    ///
+    /// # Panics
+    /// Panics when the request is invalid, see [IssuanceRequestBuilder::try_build]
     pub fn build(self) -> IrmaRequest {
-        debug_assert!(!self.credentials.is_empty());
-        IrmaRequest::Issuance {
-            credentials: self.credentials,
-            base: self.base.build(),
+        self.try_build()
+            .unwrap_or_else(|e| panic!("Invalid issuance request: {}", e))
+    }
+
+    /// Construct the actual request based on the given information, failing when it contains no
+    /// credentials, or when the attributes to be disclosed contain empty disjunctions or
+    /// conjunctions
+    pub fn try_build(self) -> Result<IrmaRequest, RequestValidationError> {
+        if self.credentials.is_empty() {
+            return Err(RequestValidationError::NoCredentials);
         }
+        Ok(IrmaRequest::Issuance {
+            credentials: self.credentials,
+            base: self.base.try_build(false)?,
+        })
     }
 
     /// Add an additional credential to be issued
@@ -368,71 +1360,175 @@ impl IssuanceRequestBuilder {
         self
     }
 
-    /// Add an additional disjunction to the request
-    pub fn add_discon(mut self, discon: Vec<Vec<AttributeRequest>>) -> IssuanceRequestBuilder {
-        self.base.add_discon(discon);
-        self
-    }
-
-    /// Add multiple additional disjunctions to the request
-    pub fn add_discons(mut self, discons: ConDisCon) -> IssuanceRequestBuilder {
-        self.base.add_discons(discons);
-        self
+    /// The credentials to be issued added so far
+    pub fn credentials(&self) -> &[Credential] {
+        &self.credentials
     }
 
-    /// Add an additional labeled disjunction to the request
-    pub fn add_discon_with_label(
+    /// Add multiple additional credentials to be issued
+    pub fn add_credentials(
         mut self,
-        discon: Vec<Vec<AttributeRequest>>,
-        label: TranslatedString,
+        credentials: impl IntoIterator<Item = Credential>,
     ) -> IssuanceRequestBuilder {
-        self.base.add_discon_with_label(discon, label);
-        self
-    }
-
-    /// Set a return URL on the request
-    pub fn return_url(mut self, return_url: String) -> IssuanceRequestBuilder {
-        self.base.return_url(return_url);
-        self
-    }
-
-    /// Set an augmented return url on the request
-    pub fn augmented_return_url(mut self, return_url: String) -> IssuanceRequestBuilder {
-        self.base.augmented_return_url(return_url);
+        self.credentials.extend(credentials);
         self
     }
 }
 
+base_builder_methods!(IssuanceRequestBuilder);
+
 /// An IRMA request extended with extra information for the server on how to execute it.
 /// (Note: this interface is unstable, and might change significantly in the future)
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ExtendedIrmaRequest {
-    /// How long a session result JWT should be valid once requested, in seconds
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub validity: Option<u64>,
-    /// How long the session remains available for an IRMA client to connect to
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub timeout: Option<u64>,
+    /// How long a session result JWT should be valid once requested, in seconds. None leaves it
+    /// unset, so that the defaults of the client apply, while `Some(None)` explicitly leaves it to
+    /// the irma server and is sent as null.
+    #[serde(
+        default,
+        deserialize_with = "crate::util::de_explicit",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub validity: Option<Option<u64>>,
+    /// How long the session remains available for an IRMA client to connect to, in seconds. As
+    /// with the validity, `Some(None)` explicitly leaves it to the irma server.
+    #[serde(
+        default,
+        deserialize_with = "crate::util::de_explicit",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout: Option<Option<u64>>,
     /// URL on which to recieve updates as the session status changes
     #[serde(rename = "callbackUrl", skip_serializing_if = "Option::is_none")]
     pub callback_url: Option<String>,
+    /// Session to start once this one has finished successfully, for chaining sessions
+    #[serde(rename = "nextSession", skip_serializing_if = "Option::is_none")]
+    pub next_session: Option<NextSessionData>,
+    /// Host (with optional port) to use in the session pointer given to the IRMA app, instead of
+    /// that of the irma server configuration, for irma servers serving multiple domains. Must be a
+    /// bare `host[:port]`, without scheme or path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
     /// Inner request
     pub request: IrmaRequest,
 }
 
+impl ExtendedIrmaRequest {
+    /// The request in the compact JSON form sent to the irma server
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string(self).map_err(Error::RequestSerialization)
+    }
+
+    /// The request as indented JSON, as also produced by its [Display] implementation
+    pub fn to_json_pretty(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).map_err(Error::RequestSerialization)
+    }
+}
+
+impl Display for ExtendedIrmaRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_json_pretty() {
+            Ok(json) => f.write_str(&json),
+            Err(_) => write!(f, "{:?}", self),
+        }
+    }
+}
+
+/// Where the irma server obtains the request for the next session of a chain
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NextSessionData {
+    /// URL to which the irma server posts the result of the finished session, responding with
+    /// the request for the next session (or with no content to end the chain). It is called by
+    /// the irma server itself, so it needs to be reachable from the irma server, not from the
+    /// browser of the user.
+    pub url: String,
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Request with which a session can be started, either a plain [IrmaRequest] or an
+/// [ExtendedIrmaRequest]. This trait is sealed, and cannot be implemented outside of this crate.
+pub trait StartSessionRequest: Serialize + Sync + sealed::Sealed {
+    #[doc(hidden)]
+    fn to_extended(&self) -> ExtendedIrmaRequest;
+
+    #[doc(hidden)]
+    fn irma_request(&self) -> &IrmaRequest;
+
+    #[doc(hidden)]
+    fn check_options(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl sealed::Sealed for IrmaRequest {}
+
+impl StartSessionRequest for IrmaRequest {
+    fn to_extended(&self) -> ExtendedIrmaRequest {
+        ExtendedIrmaRequest {
+            validity: None,
+            timeout: None,
+            callback_url: None,
+            next_session: None,
+            host: None,
+            request: self.clone(),
+        }
+    }
+
+    fn irma_request(&self) -> &IrmaRequest {
+        self
+    }
+}
+
+impl sealed::Sealed for ExtendedIrmaRequest {}
+
+impl StartSessionRequest for ExtendedIrmaRequest {
+    fn to_extended(&self) -> ExtendedIrmaRequest {
+        self.clone()
+    }
+
+    fn irma_request(&self) -> &IrmaRequest {
+        &self.request
+    }
+
+    // Check the options that the irma server would otherwise reject (or misinterpret) itself
+    fn check_options(&self) -> Result<(), Error> {
+        if let Some(host) = &self.host {
+            let bare = !host.is_empty()
+                && !host
+                    .contains(|c: char| matches!(c, '/' | '?' | '#' | '@') || c.is_whitespace());
+            // The host and port themselves are checked by parsing them as part of a url
+            if !bare || Url::parse(&format!("http://{}", host)).is_err() {
+                return Err(Error::InvalidHost(host.clone()));
+            }
+        }
+        if let Some(callback_url) = &self.callback_url {
+            if !is_http_url(callback_url) {
+                return Err(Error::InvalidCallbackUrl(callback_url.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
+    use std::time::{Duration, SystemTime};
 
     use maplit::hashmap;
+    use serde::Serialize;
 
-    use crate::CredentialBuilder;
+    use crate::{CredentialBuilder, RequestLoadError};
 
     use super::{
-        AttributeRequest, Credential, DisclosureRequestBuilder, IssuanceRequestBuilder,
-        SignatureRequestBuilder, TranslatedString,
+        all_of, any_of, one_of_sets, AttributeRequest, AttributeValue, BaseRequest, Choice,
+        ConDisCon, Credential, DisclosureRequestBuilder, ExtendedIrmaRequest, IrmaRequest,
+        IssuanceRequestBuilder, NextSessionData, SignatureRequestBuilder, StartSessionRequest,
+        TranslatedString,
     };
+    use crate::{condiscon, Error, InvalidIdentifier, RequestValidationError, SessionType};
 
     #[test]
     fn test_attribute_request() {
@@ -443,7 +1539,7 @@ mod tests {
             serde_json::from_str(&serde_json::to_string(&attr1).unwrap()).unwrap()
         );
 
-        let attr2 = AttributeRequest::non_null("x.y.z.d".into());
+        let attr2 = AttributeRequest::non_null("x.y.z.d");
         assert_eq!(
             "{\"type\":\"x.y.z.d\",\"notNull\":true}",
             serde_json::to_string(&attr2).unwrap()
@@ -453,7 +1549,7 @@ mod tests {
             serde_json::from_str(&serde_json::to_string(&attr2).unwrap()).unwrap()
         );
 
-        let attr3 = AttributeRequest::with_value("f.g.h.i".into(), "testvalue".into());
+        let attr3 = AttributeRequest::with_value("f.g.h.i", "testvalue");
         assert_eq!(
             "{\"type\":\"f.g.h.i\",\"value\":\"testvalue\"}",
             serde_json::to_string(&attr3).unwrap()
@@ -464,6 +1560,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_condiscon() {
+        let mut discons = ConDisCon::new();
+        assert!(discons.is_empty());
+        discons.push_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]]);
+        discons.push_discon(vec![
+            vec![AttributeRequest::Simple("e.f.g.h".into())],
+            vec![AttributeRequest::non_null("x.y.z.w")],
+        ]);
+        assert_eq!(discons.len(), 2);
+        assert_eq!(discons.iter().map(Vec::len).collect::<Vec<_>>(), [1, 2]);
+
+        // Vec methods are available through Deref
+        assert_eq!(discons[1][0][0], AttributeRequest::Simple("e.f.g.h".into()));
+        discons.push(vec![]);
+        assert_eq!(discons.pop(), Some(vec![]));
+        assert_eq!(discons.last().map(Vec::len), Some(2));
+
+        let json = "[[[\"a.b.c.d\"]],[[\"e.f.g.h\"],[{\"type\":\"x.y.z.w\",\"notNull\":true}]]]";
+        assert_eq!(serde_json::to_string(&discons).unwrap(), json);
+        assert_eq!(discons, serde_json::from_str(json).unwrap());
+
+        // Nested vectors are still accepted by the builders
+        #[allow(deprecated)]
+        let raw: super::RawConDisCon = discons.clone().into();
+        assert_eq!(
+            DisclosureRequestBuilder::new().add_discons(raw).build(),
+            DisclosureRequestBuilder::new().add_discons(discons).build()
+        );
+    }
+
+    #[test]
+    fn test_attribute_request_conversions() {
+        assert_eq!(
+            AttributeRequest::from("a.b.c.d"),
+            AttributeRequest::Simple("a.b.c.d".into())
+        );
+        assert_eq!(
+            AttributeRequest::from(String::from("a.b.c.d")),
+            AttributeRequest::Simple("a.b.c.d".into())
+        );
+        assert_eq!(
+            serde_json::to_string(&AttributeRequest::from(("f.g.h.i", "testvalue"))).unwrap(),
+            serde_json::to_string(&AttributeRequest::with_value("f.g.h.i", "testvalue")).unwrap()
+        );
+
+        let req = DisclosureRequestBuilder::new()
+            .add_attribute("a.b.c.d")
+            .add_choice(
+                Choice::new()
+                    .option(["e.f.g.h"])
+                    .option([("f.g.h.i", "yes")]),
+            )
+            .build();
+        assert_eq!("{\"@context\":\"https://irma.app/ld/request/disclosure/v2\",\"disclose\":[[[\"a.b.c.d\"]],[[\"e.f.g.h\"],[{\"type\":\"f.g.h.i\",\"value\":\"yes\"}]]]}", serde_json::to_string(&req).unwrap());
+    }
+
     #[test]
     fn test_credential() {
         let cred1 = CredentialBuilder::new("a.b.c".into())
@@ -485,7 +1638,7 @@ mod tests {
         assert_eq!(
             format!(
                 "{{\"credential\":\"a.b.c\",\"validity\":{},\"attributes\":{{\"d\":\"e\"}}}}",
-                cred2.validity.clone().unwrap()
+                cred2.validity.unwrap()
             ),
             serde_json::to_string(&cred2).unwrap()
         );
@@ -493,6 +1646,299 @@ mod tests {
             cred2,
             serde_json::from_str(&serde_json::to_string(&cred2).unwrap()).unwrap()
         );
+
+        let cred3 = CredentialBuilder::new("a.b.c".into())
+            .attribute("d".into(), "e".into())
+            .revocation_key("permit-1234".into())
+            .build();
+        assert_eq!(
+            "{\"credential\":\"a.b.c\",\"attributes\":{\"d\":\"e\"},\"revocationKey\":\"permit-1234\"}",
+            serde_json::to_string(&cred3).unwrap()
+        );
+        assert_eq!(
+            cred3,
+            serde_json::from_str(&serde_json::to_string(&cred3).unwrap()).unwrap()
+        );
+
+        let cred4 = CredentialBuilder::new("a.b.c".into())
+            .attribute("d".into(), "e".into())
+            .random_blind_attribute("f".into())
+            .build();
+        assert_eq!(cred4.attributes["f"], AttributeValue::RandomBlind);
+        assert_eq!(
+            "{\"credential\":\"a.b.c\",\"attributes\":{\"d\":\"e\"}}",
+            serde_json::to_string(&cred4).unwrap()
+        );
+        let parsed: Credential =
+            serde_json::from_str(r#"{"credential":"a.b.c","attributes":{"d":"e","f":null}}"#)
+                .unwrap();
+        assert_eq!(parsed, cred4);
+
+        // Value, explicitly empty and absent
+        let cred5 = CredentialBuilder::new("a.b.c".into())
+            .attribute("d".into(), "e".into())
+            .empty_attribute("f".into())
+            .random_blind_attribute("g".into())
+            .build();
+        assert_eq!(cred5.attributes["f"], AttributeValue::Empty);
+        let json = serde_json::to_value(&cred5).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"credential": "a.b.c", "attributes": {"d": "e", "f": ""}})
+        );
+        let parsed: Credential = serde_json::from_str(
+            r#"{"credential":"a.b.c","attributes":{"d":"e","f":"","g":null}}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed, cred5);
+
+        // An empty value is explicitly empty, so that it survives a roundtrip
+        let cred6 = CredentialBuilder::new("a.b.c".into())
+            .attribute("d".into(), "".into())
+            .attributes([("e", "")])
+            .build();
+        assert_eq!(cred6.attributes["d"], AttributeValue::Empty);
+        assert_eq!(cred6.attributes["e"], AttributeValue::Empty);
+        assert_eq!(AttributeValue::from(""), AttributeValue::Empty);
+        assert_eq!(
+            cred6,
+            serde_json::from_str(&serde_json::to_string(&cred6).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_validity_until() {
+        let until = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let cred = CredentialBuilder::new("a.b.c".into())
+            .validity_until(until)
+            .attribute("d".into(), "e".into())
+            .build();
+        assert_eq!(cred.validity, Some(1_000_000_000));
+        // Rounded down to a week boundary, which fall on thursdays counting from the epoch
+        assert_eq!(
+            cred.effective_validity(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(999_734_400))
+        );
+
+        let on_boundary = CredentialBuilder::new("a.b.c".into())
+            .validity_until(SystemTime::UNIX_EPOCH + Duration::from_secs(1653 * 604_800))
+            .build();
+        assert_eq!(
+            on_boundary.effective_validity(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(999_734_400))
+        );
+        assert_eq!(
+            CredentialBuilder::new("a.b.c".into())
+                .build()
+                .effective_validity(),
+            None
+        );
+
+        assert_eq!(
+            CredentialBuilder::new("a.b.c".into())
+                .validity_until(SystemTime::UNIX_EPOCH - Duration::from_secs(1))
+                .try_build(),
+            Err(RequestValidationError::ValidityBeforeEpoch("a.b.c".into()))
+        );
+    }
+
+    #[test]
+    fn test_attributes_from() {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Member {
+            full_name: String,
+            #[serde(rename = "number")]
+            membership_number: u32,
+            active: bool,
+            nickname: Option<String>,
+        }
+
+        let cred = CredentialBuilder::new("a.b.member".into())
+            .attributes_from(&Member {
+                full_name: "Alice".into(),
+                membership_number: 42,
+                active: true,
+                nickname: None,
+            })
+            .unwrap()
+            .build();
+        assert_eq!(
+            cred.attributes,
+            hashmap! {
+                "fullName".into() => "Alice".into(),
+                "number".into() => "42".into(),
+                "active".into() => "yes".into(),
+            }
+        );
+
+        #[derive(Serialize)]
+        struct Nested {
+            name: String,
+            address: Vec<String>,
+        }
+        assert!(matches!(
+            CredentialBuilder::new("a.b.c".into()).attributes_from(&Nested {
+                name: "Alice".into(),
+                address: vec!["Street 1".into()],
+            }),
+            Err(Error::NestedAttributeValue(field)) if field == "address"
+        ));
+        assert!(matches!(
+            CredentialBuilder::new("a.b.c".into()).attributes_from(&"not a struct"),
+            Err(Error::InvalidAttributes(_))
+        ));
+    }
+
+    #[test]
+    fn test_bulk_attributes() {
+        let from_map = CredentialBuilder::new("a.b.c".into())
+            .attributes(hashmap! {"d" => "e", "f" => "g"})
+            .build();
+        let from_vec = CredentialBuilder::new("a.b.c".into())
+            .attributes(vec![("d", String::from("e")), ("f", String::from("g"))])
+            .build();
+        assert_eq!(from_map, from_vec);
+        assert_eq!(
+            from_map,
+            CredentialBuilder::new("a.b.c".into())
+                .attribute("d".into(), "e".into())
+                .attribute("f".into(), "g".into())
+                .build()
+        );
+
+        assert_eq!(
+            CredentialBuilder::new("a.b.c".into())
+                .attribute("d".into(), "e".into())
+                .attributes([("d", "f")])
+                .try_build(),
+            Err(RequestValidationError::DuplicateAttribute {
+                credential: "a.b.c".into(),
+                attribute: "d".into()
+            })
+        );
+        assert!(matches!(
+            CredentialBuilder::new("a.b.c.d".into())
+                .attribute("e".into(), "f".into())
+                .try_build(),
+            Err(RequestValidationError::InvalidCredentialType(
+                InvalidIdentifier::SegmentCount { found: 4, .. }
+            ))
+        ));
+        let credential = CredentialBuilder::from_identifier("a.b.c".parse().unwrap())
+            .attributes([("d", "e"), ("f", "g")])
+            .build();
+        assert_eq!(credential, from_map);
+
+        let req = IssuanceRequestBuilder::new()
+            .add_credentials((0..2).map(|i| {
+                CredentialBuilder::new("a.b.c".into())
+                    .attribute("d".into(), i.to_string())
+                    .build()
+            }))
+            .build();
+        assert_eq!("{\"@context\":\"https://irma.app/ld/request/issuance/v2\",\"credentials\":[{\"credential\":\"a.b.c\",\"attributes\":{\"d\":\"0\"}},{\"credential\":\"a.b.c\",\"attributes\":{\"d\":\"1\"}}]}", serde_json::to_string(&req).unwrap());
+
+        let req = DisclosureRequestBuilder::new()
+            .add_attributes(["a.b.c.d", "e.f.g.h"])
+            .build();
+        assert_eq!(
+            req,
+            DisclosureRequestBuilder::new()
+                .add_attribute("a.b.c.d")
+                .add_attribute("e.f.g.h")
+                .build()
+        );
+    }
+
+    #[test]
+    fn test_start_session_request() {
+        fn wire<R: StartSessionRequest>(request: &R) -> String {
+            serde_json::to_string(request).unwrap()
+        }
+
+        let req = DisclosureRequestBuilder::new()
+            .add_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]])
+            .build();
+        assert_eq!(
+            wire(&req),
+            "{\"@context\":\"https://irma.app/ld/request/disclosure/v2\",\"disclose\":[[[\"a.b.c.d\"]]]}"
+        );
+        assert_eq!(wire(&req), serde_json::to_string(&req).unwrap());
+
+        let extended = ExtendedIrmaRequest {
+            validity: Some(Some(60)),
+            timeout: None,
+            callback_url: Some("https://example.com/callback".into()),
+            next_session: None,
+            host: None,
+            request: req,
+        };
+        assert_eq!(
+            wire(&extended),
+            "{\"validity\":60,\"callbackUrl\":\"https://example.com/callback\",\"request\":{\"@context\":\"https://irma.app/ld/request/disclosure/v2\",\"disclose\":[[[\"a.b.c.d\"]]]}}"
+        );
+        assert_eq!(wire(&extended), serde_json::to_string(&extended).unwrap());
+    }
+
+    #[test]
+    fn test_extended_request_host() {
+        let mut extended = DisclosureRequestBuilder::new()
+            .add_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]])
+            .build()
+            .to_extended();
+        assert!(!serde_json::to_string(&extended).unwrap().contains("host"));
+
+        extended.host = Some("irma.example.com:8443".into());
+        assert!(extended.check_options().is_ok());
+        let json = serde_json::to_string(&extended).unwrap();
+        assert_eq!(
+            json,
+            "{\"host\":\"irma.example.com:8443\",\"request\":{\"@context\":\"https://irma.app/ld/request/disclosure/v2\",\"disclose\":[[[\"a.b.c.d\"]]]}}"
+        );
+        assert_eq!(
+            extended,
+            serde_json::from_str::<ExtendedIrmaRequest>(&json).unwrap()
+        );
+
+        for invalid in [
+            "https://irma.example.com",
+            "irma.example.com/irma",
+            "irma.example.com:port",
+            "user@irma.example.com",
+            "",
+        ] {
+            extended.host = Some(invalid.into());
+            assert!(matches!(
+                extended.check_options(),
+                Err(Error::InvalidHost(host)) if host == invalid
+            ));
+        }
+    }
+
+    #[test]
+    fn test_extended_request_next_session() {
+        let extended = ExtendedIrmaRequest {
+            validity: Some(None),
+            timeout: Some(Some(120)),
+            callback_url: Some("https://example.com/callback".into()),
+            next_session: Some(NextSessionData {
+                url: "https://backend.example.com/next".into(),
+            }),
+            host: None,
+            request: DisclosureRequestBuilder::new()
+                .add_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]])
+                .build(),
+        };
+        let json = serde_json::to_string(&extended).unwrap();
+        assert_eq!(
+            json,
+            "{\"validity\":null,\"timeout\":120,\"callbackUrl\":\"https://example.com/callback\",\"nextSession\":{\"url\":\"https://backend.example.com/next\"},\"request\":{\"@context\":\"https://irma.app/ld/request/disclosure/v2\",\"disclose\":[[[\"a.b.c.d\"]]]}}"
+        );
+        assert_eq!(
+            extended,
+            serde_json::from_str::<ExtendedIrmaRequest>(&json).unwrap()
+        );
     }
 
     #[test]
@@ -507,7 +1953,7 @@ mod tests {
         );
 
         let req2 = DisclosureRequestBuilder::new()
-            .add_discon(vec![vec![AttributeRequest::non_null("x.y.z.w".into())]])
+            .add_discon(vec![vec![AttributeRequest::non_null("x.y.z.w")]])
             .add_discon_with_label(
                 vec![vec![AttributeRequest::Simple("a.b.c.d".into())]],
                 TranslatedString {
@@ -541,6 +1987,39 @@ mod tests {
             req4,
             serde_json::from_str(&serde_json::to_string(&req4).unwrap()).unwrap()
         );
+
+        let req5 = DisclosureRequestBuilder::new()
+            .add_discon(vec![vec![AttributeRequest::Simple(
+                "irma-demo.MijnOverheid.root.BSN".into(),
+            )]])
+            .require_nonrevocation("irma-demo.MijnOverheid.root".into())
+            .require_nonrevocation("irma-demo.MijnOverheid.root".into())
+            .build();
+        assert_eq!("{\"@context\":\"https://irma.app/ld/request/disclosure/v2\",\"disclose\":[[[\"irma-demo.MijnOverheid.root.BSN\"]]],\"revocation\":[\"irma-demo.MijnOverheid.root\"]}", serde_json::to_string(&req5).unwrap());
+        assert_eq!(
+            req5,
+            serde_json::from_str(
+                r#"{
+                    "@context": "https://irma.app/ld/request/disclosure/v2",
+                    "disclose": [[["irma-demo.MijnOverheid.root.BSN"]]],
+                    "revocation": ["irma-demo.MijnOverheid.root"]
+                }"#
+            )
+            .unwrap()
+        );
+
+        let req6 = DisclosureRequestBuilder::new()
+            .add_discon(vec![vec![AttributeRequest::Simple(
+                "irma-demo.MijnOverheid.root.BSN".into(),
+            )]])
+            .skip_expiry_check("irma-demo.MijnOverheid.root".into())
+            .skip_expiry_check("irma-demo.MijnOverheid.root".into())
+            .build();
+        assert_eq!("{\"@context\":\"https://irma.app/ld/request/disclosure/v2\",\"disclose\":[[[\"irma-demo.MijnOverheid.root.BSN\"]]],\"skipExpiryCheck\":[\"irma-demo.MijnOverheid.root\"]}", serde_json::to_string(&req6).unwrap());
+        assert_eq!(
+            req6,
+            serde_json::from_str(&serde_json::to_string(&req6).unwrap()).unwrap()
+        );
     }
 
     #[test]
@@ -588,17 +2067,38 @@ mod tests {
             req4,
             serde_json::from_str(&serde_json::to_string(&req4).unwrap()).unwrap()
         );
+
+        let req5 = SignatureRequestBuilder::new("testmessage".into())
+            .add_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]])
+            .require_nonrevocation("a.b.c".into())
+            .build();
+        assert_eq!("{\"@context\":\"https://irma.app/ld/request/signature/v2\",\"message\":\"testmessage\",\"disclose\":[[[\"a.b.c.d\"]]],\"revocation\":[\"a.b.c\"]}", serde_json::to_string(&req5).unwrap());
+        assert_eq!(
+            req5,
+            serde_json::from_str(&serde_json::to_string(&req5).unwrap()).unwrap()
+        );
+
+        let req6 = SignatureRequestBuilder::new("testmessage".into())
+            .add_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]])
+            .skip_expiry_check("a.b.c".into())
+            .build();
+        assert_eq!("{\"@context\":\"https://irma.app/ld/request/signature/v2\",\"message\":\"testmessage\",\"disclose\":[[[\"a.b.c.d\"]]],\"skipExpiryCheck\":[\"a.b.c\"]}", serde_json::to_string(&req6).unwrap());
+        assert_eq!(
+            req6,
+            serde_json::from_str(&serde_json::to_string(&req6).unwrap()).unwrap()
+        );
     }
 
     #[test]
     fn test_issuance_request() {
         let req1 = IssuanceRequestBuilder::new()
             .add_credential(Credential {
-                credential: "a.b.c".into(),
+                credential: "a.b.c".parse().unwrap(),
                 validity: Some(123456789),
                 attributes: hashmap![
                     "d".into() => "e".into(),
                 ],
+                revocation_key: None,
             })
             .build();
         assert_eq!("{\"@context\":\"https://irma.app/ld/request/issuance/v2\",\"credentials\":[{\"credential\":\"a.b.c\",\"validity\":123456789,\"attributes\":{\"d\":\"e\"}}]}", serde_json::to_string(&req1).unwrap());
@@ -610,11 +2110,12 @@ mod tests {
         let req2 = IssuanceRequestBuilder::new()
             .add_discon(vec![vec![AttributeRequest::Simple("x.y.z.w".into())]])
             .add_credential(Credential {
-                credential: "a.b.c".into(),
+                credential: "a.b.c".parse().unwrap(),
                 validity: Some(123456789),
                 attributes: hashmap![
                     "d".into() => "e".into(),
                 ],
+                revocation_key: None,
             })
             .build();
         assert_eq!("{\"@context\":\"https://irma.app/ld/request/issuance/v2\",\"credentials\":[{\"credential\":\"a.b.c\",\"validity\":123456789,\"attributes\":{\"d\":\"e\"}}],\"disclose\":[[[\"x.y.z.w\"]]]}", serde_json::to_string(&req2).unwrap());
@@ -632,11 +2133,12 @@ mod tests {
                 },
             )
             .add_credential(Credential {
-                credential: "a.b.c".into(),
+                credential: "a.b.c".parse().unwrap(),
                 validity: Some(123456789),
                 attributes: hashmap![
                     "d".into() => "e".into(),
                 ],
+                revocation_key: None,
             })
             .build();
         assert_eq!("{\"@context\":\"https://irma.app/ld/request/issuance/v2\",\"credentials\":[{\"credential\":\"a.b.c\",\"validity\":123456789,\"attributes\":{\"d\":\"e\"}}],\"disclose\":[[[\"x.y.z.w\"]]],\"labels\":{\"0\":{\"en\":\"en\",\"nl\":\"nl\"}}}", serde_json::to_string(&req3).unwrap());
@@ -647,11 +2149,12 @@ mod tests {
 
         let req4 = IssuanceRequestBuilder::new()
             .add_credential(Credential {
-                credential: "a.b.c".into(),
+                credential: "a.b.c".parse().unwrap(),
                 validity: Some(123456789),
                 attributes: hashmap![
                     "d".into() => "e".into(),
                 ],
+                revocation_key: None,
             })
             .return_url("https://example.com".into())
             .build();
@@ -663,11 +2166,12 @@ mod tests {
 
         let req5 = IssuanceRequestBuilder::new()
             .add_credential(Credential {
-                credential: "a.b.c".into(),
+                credential: "a.b.c".parse().unwrap(),
                 validity: Some(123456789),
                 attributes: hashmap![
                     "d".into() => "e".into(),
                 ],
+                revocation_key: None,
             })
             .augmented_return_url("https://example.com".into())
             .build();
@@ -679,11 +2183,12 @@ mod tests {
 
         let req6 = IssuanceRequestBuilder::new()
             .add_credential(Credential {
-                credential: "a.b.c".into(),
+                credential: "a.b.c".parse().unwrap(),
                 validity: None,
                 attributes: hashmap![
                     "d".into() => "e".into(),
                 ],
+                revocation_key: None,
             })
             .build();
         assert_eq!("{\"@context\":\"https://irma.app/ld/request/issuance/v2\",\"credentials\":[{\"credential\":\"a.b.c\",\"attributes\":{\"d\":\"e\"}}]}", serde_json::to_string(&req6).unwrap());
@@ -692,4 +2197,1087 @@ mod tests {
             serde_json::from_str(&serde_json::to_string(&req6).unwrap()).unwrap()
         );
     }
+
+    #[test]
+    fn test_label_conversions() {
+        let req = DisclosureRequestBuilder::new()
+            .add_attribute_with_label("a.b.c.d", "Email")
+            .add_discon_with_label(
+                vec![vec![AttributeRequest::Simple("e.f.g.h".into())]],
+                TranslatedString::new("Phone", "Telefoon"),
+            )
+            .add_attribute("x.y.z.w")
+            .set_label(2, String::from("Name"))
+            .build();
+        // The order of the labels in the JSON is unspecified, so compare parsed values
+        assert_eq!(
+            serde_json::to_value(&req).unwrap(),
+            serde_json::json!({
+                "@context": "https://irma.app/ld/request/disclosure/v2",
+                "disclose": [[["a.b.c.d"]], [["e.f.g.h"]], [["x.y.z.w"]]],
+                "labels": {
+                    "0": {"en": "Email", "nl": "Email"},
+                    "1": {"en": "Phone", "nl": "Telefoon"},
+                    "2": {"en": "Name", "nl": "Name"}
+                }
+            })
+        );
+        assert_eq!(
+            TranslatedString::new("Phone", "Telefoon").to_string(),
+            "Phone"
+        );
+        assert_eq!(
+            TranslatedString::new("", "Telefoon").to_string(),
+            "Telefoon"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let valid = DisclosureRequestBuilder::new()
+            .add_discon(vec![
+                vec![AttributeRequest::Simple("a.b.c.d".into())],
+                vec![
+                    AttributeRequest::Simple("e.f.g.h".into()),
+                    AttributeRequest::non_null("e.f.g.i"),
+                ],
+            ])
+            .build();
+        assert_eq!(valid.validate(), Ok(()));
+
+        // Built without the builders, which reject some of these themselves
+        let request = |disclose: ConDisCon, labels: Vec<usize>| IrmaRequest::Disclosure {
+            base: BaseRequest {
+                disclose,
+                return_url: None,
+                augment_return: false,
+                labels: labels
+                    .into_iter()
+                    .map(|index| (index, TranslatedString::single("label")))
+                    .collect(),
+                revocation: vec![],
+                skip_expiry_check: vec![],
+            },
+        };
+
+        assert_eq!(
+            request(ConDisCon::new(), vec![]).validate(),
+            Err(vec![RequestValidationError::NoDisjunctions])
+        );
+        assert_eq!(
+            request(condiscon![[["a.b.c.d"]], []], vec![]).validate(),
+            Err(vec![RequestValidationError::EmptyDisjunction {
+                disjunction: 1
+            }])
+        );
+        assert_eq!(
+            request(condiscon![[["a.b.c.d"], [], ["e.f.g.h"]]], vec![]).validate(),
+            Err(vec![RequestValidationError::EmptyInnerConjunction {
+                disjunction: 0,
+                conjunction: 1
+            }])
+        );
+        assert_eq!(
+            request(
+                condiscon![[["e.f.g.h"]], [["a.b.c.d"], ["e.f.g.h", { "a.b.c.d" => "x" }, "e.f.g.h"]]],
+                vec![]
+            )
+            .validate(),
+            Err(vec![RequestValidationError::RepeatedAttribute {
+                disjunction: 1,
+                conjunction: 1,
+                attribute: "e.f.g.h".into()
+            }])
+        );
+        let pinned = AttributeRequest::Compound {
+            attr_type: "a.b.c.d".into(),
+            value: Some("yes".into()),
+            not_null: true,
+        };
+        assert_eq!(
+            request(ConDisCon::from(vec![vec![vec![pinned]]]), vec![]).validate(),
+            Err(vec![RequestValidationError::ValueAndNotNull {
+                disjunction: 0,
+                conjunction: 0,
+                attribute: "a.b.c.d".into()
+            }])
+        );
+        assert_eq!(
+            request(condiscon![[["a.b.c.d"]]], vec![0, 3, 1]).validate(),
+            Err(vec![
+                RequestValidationError::LabelOutOfRange {
+                    index: 1,
+                    disjunctions: 1
+                },
+                RequestValidationError::LabelOutOfRange {
+                    index: 3,
+                    disjunctions: 1
+                },
+            ])
+        );
+
+        // The builders report the first of the same issues
+        for disclose in [
+            ConDisCon::new(),
+            condiscon![[["a.b.c.d"]], []],
+            condiscon![[["a.b.c.d"], [], ["e.f.g.h"]]],
+        ] {
+            assert_eq!(
+                DisclosureRequestBuilder::new()
+                    .add_discons(disclose.clone())
+                    .try_build()
+                    .map(|_| ()),
+                request(disclose, vec![])
+                    .validate()
+                    .map_err(|issues| issues[0].clone())
+            );
+        }
+
+        // All issues are reported at once
+        assert_eq!(
+            request(condiscon![[[]], []], vec![2]).validate(),
+            Err(vec![
+                RequestValidationError::EmptyInnerConjunction {
+                    disjunction: 0,
+                    conjunction: 0
+                },
+                RequestValidationError::EmptyDisjunction { disjunction: 1 },
+                RequestValidationError::LabelOutOfRange {
+                    index: 2,
+                    disjunctions: 2
+                },
+            ])
+        );
+
+        let issuance = IrmaRequest::Issuance {
+            credentials: vec![],
+            base: valid.base().clone(),
+        };
+        assert_eq!(
+            issuance.validate(),
+            Err(vec![RequestValidationError::NoCredentials])
+        );
+        let signature = IrmaRequest::Signature {
+            message: "testmessage".into(),
+            base: valid.base().clone(),
+        };
+        assert_eq!(signature.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_request_accessors() {
+        let disclosure = DisclosureRequestBuilder::new()
+            .add_attribute("a.b.c.d")
+            .return_url("https://example.com".into())
+            .build();
+        assert_eq!(disclosure.session_type(), SessionType::Disclosing);
+        assert_eq!(disclosure.disclose().len(), 1);
+        assert_eq!(disclosure.return_url(), Some("https://example.com"));
+        assert_eq!(disclosure.message(), None);
+        assert!(disclosure.credentials().is_none());
+
+        let signature = SignatureRequestBuilder::new("testmessage".into())
+            .add_attribute("a.b.c.d")
+            .add_attribute("e.f.g.h")
+            .build();
+        assert_eq!(signature.session_type(), SessionType::Signing);
+        assert_eq!(signature.disclose().len(), 2);
+        assert_eq!(signature.message(), Some("testmessage"));
+        assert_eq!(signature.return_url(), None);
+        assert!(signature.credentials().is_none());
+
+        let credential = CredentialBuilder::new("a.b.c".into())
+            .attribute("d".into(), "e".into())
+            .build();
+        let issuance = IssuanceRequestBuilder::new()
+            .add_credential(credential.clone())
+            .build();
+        assert_eq!(issuance.session_type(), SessionType::Issuing);
+        assert!(issuance.disclose().is_empty());
+        assert_eq!(issuance.credentials(), Some(&[credential][..]));
+        assert_eq!(issuance.message(), None);
+        assert!(issuance.base().labels.is_empty());
+    }
+
+    #[test]
+    fn test_request_constructors() {
+        let disclosure = IrmaRequest::disclosure(condiscon![[["a.b.c.d"]]]).unwrap();
+        assert_eq!(
+            serde_json::to_string(&disclosure).unwrap(),
+            serde_json::to_string(
+                &DisclosureRequestBuilder::new()
+                    .add_attribute("a.b.c.d")
+                    .build()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            IrmaRequest::disclosure(ConDisCon::new()),
+            Err(RequestValidationError::NoDisjunctions)
+        );
+
+        let signature = IrmaRequest::signature("testmessage", condiscon![[["a.b.c.d"]]]).unwrap();
+        assert_eq!(
+            serde_json::to_string(&signature).unwrap(),
+            serde_json::to_string(
+                &SignatureRequestBuilder::new("testmessage".into())
+                    .add_attribute("a.b.c.d")
+                    .build()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            IrmaRequest::signature("testmessage", condiscon![[]]),
+            Err(RequestValidationError::EmptyDisjunction { disjunction: 0 })
+        );
+
+        let credential = CredentialBuilder::new("a.b.c".into())
+            .attribute("d".into(), "e".into())
+            .build();
+        let issuance = IrmaRequest::issuance(vec![credential.clone()]).unwrap();
+        assert_eq!(
+            serde_json::to_string(&issuance).unwrap(),
+            serde_json::to_string(
+                &IssuanceRequestBuilder::new()
+                    .add_credential(credential)
+                    .build()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            IrmaRequest::issuance(vec![]),
+            Err(RequestValidationError::NoCredentials)
+        );
+    }
+
+    #[test]
+    fn test_return_url_validation() {
+        let req = DisclosureRequestBuilder::new()
+            .add_attribute("a.b.c.d")
+            .augmented_return_url("https://example.com/done?x=1".into())
+            .build();
+        assert_eq!(req.return_url(), Some("https://example.com/done?x=1"));
+
+        for invalid in [
+            "htps://example.com",
+            "/done",
+            "example.com/done",
+            "https://",
+        ] {
+            assert_eq!(
+                DisclosureRequestBuilder::new()
+                    .add_attribute("a.b.c.d")
+                    .return_url(invalid.into())
+                    .try_build(),
+                Err(RequestValidationError::InvalidReturnUrl(invalid.into()))
+            );
+        }
+        assert_eq!(
+            SignatureRequestBuilder::new("testmessage".into())
+                .add_attribute("a.b.c.d")
+                .augmented_return_url("myapp://done".into())
+                .try_build(),
+            Err(RequestValidationError::InvalidReturnUrl(
+                "myapp://done".into()
+            ))
+        );
+
+        let req = IssuanceRequestBuilder::new()
+            .add_credential(CredentialBuilder::new("a.b.c".into()).build())
+            .return_url_unchecked("myapp://done".into())
+            .build();
+        assert_eq!(req.return_url(), Some("myapp://done"));
+    }
+
+    #[test]
+    fn test_extended_request_callback_url() {
+        let mut extended = DisclosureRequestBuilder::new()
+            .add_attribute("a.b.c.d")
+            .build()
+            .to_extended();
+        extended.callback_url = Some("https://example.com/callback".into());
+        assert!(extended.check_options().is_ok());
+        for invalid in ["htps://example.com/callback", "/callback"] {
+            extended.callback_url = Some(invalid.into());
+            assert!(matches!(
+                extended.check_options(),
+                Err(Error::InvalidCallbackUrl(url)) if url == invalid
+            ));
+        }
+    }
+
+    #[test]
+    fn test_return_url_set_twice() {
+        let twice = RequestValidationError::ReturnUrlSetTwice {
+            first: "https://example.com/first".into(),
+            second: "https://example.com/second".into(),
+        };
+        assert_eq!(
+            DisclosureRequestBuilder::new()
+                .add_attribute("a.b.c.d")
+                .return_url("https://example.com/first".into())
+                .augmented_return_url("https://example.com/second".into())
+                .try_build(),
+            Err(twice.clone())
+        );
+        assert_eq!(
+            SignatureRequestBuilder::new("testmessage".into())
+                .add_attribute("a.b.c.d")
+                .augmented_return_url("https://example.com/first".into())
+                .return_url("https://example.com/second".into())
+                .try_build(),
+            Err(twice.clone())
+        );
+        assert_eq!(
+            IssuanceRequestBuilder::new()
+                .add_credential(CredentialBuilder::new("a.b.c".into()).build())
+                .return_url("https://example.com/first".into())
+                .return_url("https://example.com/second".into())
+                .try_build(),
+            Err(twice)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "already set to https://example.com/first")]
+    fn test_return_url_set_twice_build_panics() {
+        DisclosureRequestBuilder::new()
+            .add_attribute("a.b.c.d")
+            .return_url("https://example.com/first".into())
+            .return_url("https://example.com/second".into())
+            .build();
+    }
+
+    #[test]
+    fn test_builder_template() {
+        let template = DisclosureRequestBuilder::new()
+            .add_attribute_with_label("pbdf.sidn-pbdf.email.email", "Email")
+            .add_attribute_with_label("pbdf.sidn-pbdf.mobilenumber.mobilenumber", "Phone")
+            .add_attribute_with_label("pbdf.gemeente.personalData.fullname", "Name")
+            .return_url("https://example.com/done".into());
+        assert_eq!(template.discon_count(), 3);
+        assert!(template.has_return_url());
+
+        // Without the phone number, and returning to the app instead
+        let tenant = template
+            .clone()
+            .remove_discon(1)
+            .clear_return_url()
+            .return_url_unchecked("myapp://done".into());
+        assert_eq!(tenant.discon_count(), 2);
+        assert_eq!(
+            tenant.labels(),
+            &hashmap! {0 => "Email".into(), 1 => "Name".into()}
+        );
+
+        let default = serde_json::to_value(template.build()).unwrap();
+        let tenant = serde_json::to_value(tenant.build()).unwrap();
+        assert_eq!(default["disclose"].as_array().unwrap().len(), 3);
+        assert_eq!(
+            tenant["disclose"],
+            serde_json::json!([
+                [["pbdf.sidn-pbdf.email.email"]],
+                [["pbdf.gemeente.personalData.fullname"]]
+            ])
+        );
+        assert_eq!(default["clientReturnUrl"], "https://example.com/done");
+        assert_eq!(tenant["clientReturnUrl"], "myapp://done");
+        assert_eq!(default["labels"]["0"], tenant["labels"]["0"]);
+        assert_eq!(default["labels"]["2"], tenant["labels"]["1"]);
+
+        // Clearing the return url also clears problems with earlier ones
+        assert!(SignatureRequestBuilder::new("testmessage".into())
+            .add_attribute("a.b.c.d")
+            .return_url("htps://example.com".into())
+            .clear_return_url()
+            .try_build()
+            .is_ok());
+
+        let issuance = IssuanceRequestBuilder::new()
+            .add_credential(CredentialBuilder::new("a.b.c".into()).build())
+            .remove_discon(0);
+        assert_eq!(issuance.credentials().len(), 1);
+        assert!(!issuance.has_return_url());
+    }
+
+    #[test]
+    fn test_new_json() {
+        #[derive(Serialize)]
+        struct Contract {
+            party: String,
+            amount: u32,
+            terms: Vec<&'static str>,
+        }
+
+        #[derive(Serialize)]
+        struct ReorderedContract {
+            terms: Vec<&'static str>,
+            amount: u32,
+            party: String,
+        }
+
+        let first = SignatureRequestBuilder::new_json(&Contract {
+            party: "Alice \"A\"".into(),
+            amount: 12,
+            terms: vec!["b", "a"],
+        })
+        .unwrap()
+        .add_attribute("irma-demo.MijnOverheid.root.BSN")
+        .build();
+        let second = SignatureRequestBuilder::new_json(&ReorderedContract {
+            terms: vec!["b", "a"],
+            amount: 12,
+            party: "Alice \"A\"".into(),
+        })
+        .unwrap()
+        .add_attribute("irma-demo.MijnOverheid.root.BSN")
+        .build();
+
+        let expected = r#"{"amount":12,"party":"Alice \"A\"","terms":["b","a"]}"#;
+        assert_eq!(first.message(), Some(expected));
+        assert_eq!(second.message(), Some(expected));
+
+        let map = hashmap! {
+            "z" => serde_json::json!({"y": null, "x": true}),
+            "a" => serde_json::json!(1.5),
+        };
+        assert_eq!(
+            crate::canonical_json(&map).unwrap(),
+            r#"{"a":1.5,"z":{"x":true,"y":null}}"#
+        );
+    }
+
+    #[test]
+    fn test_display_disclosure() {
+        let request = DisclosureRequestBuilder::new()
+            .add_discon_with_label(
+                vec![vec!["irma-demo.MijnOverheid.ageLimits.over18".into()]],
+                TranslatedString::new("Age", "Leeftijd"),
+            )
+            .return_url("https://example.com/done".into())
+            .build();
+
+        assert_eq!(
+            request.to_string(),
+            r#"{
+  "@context": "https://irma.app/ld/request/disclosure/v2",
+  "disclose": [
+    [
+      [
+        "irma-demo.MijnOverheid.ageLimits.over18"
+      ]
+    ]
+  ],
+  "clientReturnUrl": "https://example.com/done",
+  "labels": {
+    "0": {
+      "en": "Age",
+      "nl": "Leeftijd"
+    }
+  }
+}"#
+        );
+        assert_eq!(request.to_string(), request.to_json_pretty().unwrap());
+        assert_eq!(
+            request.to_json().unwrap(),
+            r#"{"@context":"https://irma.app/ld/request/disclosure/v2","disclose":[[["irma-demo.MijnOverheid.ageLimits.over18"]]],"clientReturnUrl":"https://example.com/done","labels":{"0":{"en":"Age","nl":"Leeftijd"}}}"#
+        );
+    }
+
+    #[test]
+    fn test_display_signature() {
+        let request = SignatureRequestBuilder::new("I agree".into())
+            .add_attribute(AttributeRequest::non_null(
+                "irma-demo.MijnOverheid.root.BSN",
+            ))
+            .build();
+
+        assert_eq!(
+            request.to_string(),
+            r#"{
+  "@context": "https://irma.app/ld/request/signature/v2",
+  "message": "I agree",
+  "disclose": [
+    [
+      [
+        {
+          "type": "irma-demo.MijnOverheid.root.BSN",
+          "notNull": true
+        }
+      ]
+    ]
+  ]
+}"#
+        );
+        assert_eq!(
+            request.to_json().unwrap(),
+            r#"{"@context":"https://irma.app/ld/request/signature/v2","message":"I agree","disclose":[[[{"type":"irma-demo.MijnOverheid.root.BSN","notNull":true}]]]}"#
+        );
+    }
+
+    #[test]
+    fn test_display_issuance() {
+        let request = IssuanceRequestBuilder::new()
+            .add_credential(
+                CredentialBuilder::new("irma-demo.MijnOverheid.root".into())
+                    .attribute("BSN".into(), "12345".into())
+                    .build(),
+            )
+            .build();
+
+        assert_eq!(
+            request.to_string(),
+            r#"{
+  "@context": "https://irma.app/ld/request/issuance/v2",
+  "credentials": [
+    {
+      "credential": "irma-demo.MijnOverheid.root",
+      "attributes": {
+        "BSN": "12345"
+      }
+    }
+  ]
+}"#
+        );
+        assert_eq!(
+            request.to_json().unwrap(),
+            r#"{"@context":"https://irma.app/ld/request/issuance/v2","credentials":[{"credential":"irma-demo.MijnOverheid.root","attributes":{"BSN":"12345"}}]}"#
+        );
+    }
+
+    #[test]
+    fn test_display_extended() {
+        let request = ExtendedIrmaRequest {
+            validity: Some(Some(60)),
+            timeout: None,
+            callback_url: None,
+            next_session: None,
+            host: None,
+            request: DisclosureRequestBuilder::new()
+                .add_attribute("irma-demo.MijnOverheid.ageLimits.over18")
+                .build(),
+        };
+
+        assert_eq!(
+            request.to_string(),
+            r#"{
+  "validity": 60,
+  "request": {
+    "@context": "https://irma.app/ld/request/disclosure/v2",
+    "disclose": [
+      [
+        [
+          "irma-demo.MijnOverheid.ageLimits.over18"
+        ]
+      ]
+    ]
+  }
+}"#
+        );
+        assert_eq!(
+            request.to_json().unwrap(),
+            r#"{"validity":60,"request":{"@context":"https://irma.app/ld/request/disclosure/v2","disclose":[[["irma-demo.MijnOverheid.ageLimits.over18"]]]}}"#
+        );
+    }
+
+    #[test]
+    fn test_deserialize_unknown_context() {
+        let err = serde_json::from_str::<IrmaRequest>(
+            r#"{"@context":"https://irma.app/ld/request/teleportation/v1","disclose":[[["irma-demo.MijnOverheid.root.BSN"]]]}"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains(r#""https://irma.app/ld/request/teleportation/v1""#));
+        assert!(err.contains("https://irma.app/ld/request/disclosure/v2"));
+        assert!(err.contains("https://irma.app/ld/request/signature/v2"));
+        assert!(err.contains("https://irma.app/ld/request/issuance/v2"));
+
+        let err = serde_json::from_str::<IrmaRequest>(
+            r#"{"disclose":[[["irma-demo.MijnOverheid.root.BSN"]]]}"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("no @context field"));
+        assert!(err.contains("https://irma.app/ld/request/disclosure/v2"));
+
+        // Known contexts still deserialize, also nested in extended requests
+        let extended = serde_json::from_str::<ExtendedIrmaRequest>(
+            r#"{"request":{"@context":"https://irma.app/ld/request/signature/v2","message":"test","disclose":[[["irma-demo.MijnOverheid.root.BSN"]]]}}"#,
+        )
+        .unwrap();
+        assert_eq!(extended.request.message(), Some("test"));
+        assert!(serde_json::from_str::<ExtendedIrmaRequest>(
+            r#"{"request":{"@context":"https://example.com","disclose":[]}}"#
+        )
+        .unwrap_err()
+        .to_string()
+        .contains(r#"unknown @context "https://example.com""#));
+    }
+
+    #[test]
+    fn test_add_optional_discon() {
+        let email = || AttributeRequest::Simple("pbdf.sidn-pbdf.email.email".into());
+
+        let disclosure = DisclosureRequestBuilder::new()
+            .add_attribute("irma-demo.MijnOverheid.ageLimits.over18")
+            .add_optional_discon(vec![vec![email()]])
+            .build();
+        assert_eq!(
+            disclosure.disclose().iter().nth(1),
+            Some(&vec![vec![email()], vec![]])
+        );
+        assert_eq!(
+            serde_json::to_string(disclosure.disclose()).unwrap(),
+            r#"[[["irma-demo.MijnOverheid.ageLimits.over18"]],[["pbdf.sidn-pbdf.email.email"],[]]]"#
+        );
+        assert_eq!(disclosure.validate(), Ok(()));
+
+        let signature = SignatureRequestBuilder::new("message".into())
+            .add_optional_discon(Choice::of(email()))
+            .build();
+        assert_eq!(
+            signature.disclose().iter().next(),
+            Some(&vec![vec![email()], vec![]])
+        );
+
+        let issuance = IssuanceRequestBuilder::new()
+            .add_credential(
+                CredentialBuilder::new("a.b.c".into())
+                    .attribute("d".into(), "e".into())
+                    .build(),
+            )
+            .add_optional_discon(vec![vec![email()]])
+            .build();
+        assert_eq!(
+            issuance.disclose().iter().next(),
+            Some(&vec![vec![email()], vec![]])
+        );
+
+        // Only a single trailing empty option after other options is allowed
+        assert_eq!(
+            DisclosureRequestBuilder::new()
+                .add_optional_discon(vec![])
+                .try_build(),
+            Err(RequestValidationError::EmptyInnerConjunction {
+                disjunction: 0,
+                conjunction: 0
+            })
+        );
+        assert_eq!(
+            DisclosureRequestBuilder::new()
+                .add_optional_discon(vec![vec![email()], vec![]])
+                .try_build(),
+            Err(RequestValidationError::EmptyInnerConjunction {
+                disjunction: 0,
+                conjunction: 1
+            })
+        );
+        let request =
+            IrmaRequest::disclosure(ConDisCon::from(vec![vec![vec![email()], vec![], vec![]]]));
+        assert!(request.is_err());
+    }
+
+    #[test]
+    fn test_from_json() {
+        let request = IrmaRequest::from_json(include_str!(
+            "../tests/fixtures/requests/valid_issuance.json"
+        ))
+        .unwrap();
+        assert_eq!(
+            request,
+            IssuanceRequestBuilder::new()
+                .add_credential(
+                    CredentialBuilder::new("irma-demo.MijnOverheid.root".into())
+                        .attribute("BSN".into(), "12345".into())
+                        .build()
+                )
+                .add_attribute_with_label(
+                    "irma-demo.MijnOverheid.ageLimits.over18",
+                    TranslatedString::new("Age", "Leeftijd")
+                )
+                .build()
+        );
+
+        let invalid = |file: &str| match IrmaRequest::from_json_file(format!(
+            "{}/tests/fixtures/requests/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            file
+        )) {
+            Err(RequestLoadError::Invalid { pointer, message }) => (pointer, message),
+            other => panic!("unexpected result for {}: {:?}", file, other),
+        };
+        assert_eq!(
+            invalid("missing_credential.json"),
+            ("/credentials/1".into(), "missing field `credential`".into())
+        );
+        assert_eq!(invalid("invalid_attribute.json").0, "/disclose/0/0/1");
+        assert_eq!(
+            invalid("missing_message.json"),
+            ("".into(), "missing field `message`".into())
+        );
+        let (pointer, message) = invalid("unknown_context.json");
+        assert_eq!(pointer, "/@context");
+        assert!(message.contains("https://irma.app/ld/request/teleportation/v1"));
+
+        let err = IrmaRequest::from_json_file(format!(
+            "{}/tests/fixtures/requests/missing_credential.json",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"Invalid request at "/credentials/1": missing field `credential`"#
+        );
+
+        // All structural problems are reported at once
+        match IrmaRequest::from_json(include_str!(
+            "../tests/fixtures/requests/empty_disjunctions.json"
+        )) {
+            Err(RequestLoadError::InvalidRequest(issues)) => assert_eq!(
+                issues,
+                vec![
+                    RequestValidationError::EmptyDisjunction { disjunction: 0 },
+                    RequestValidationError::EmptyInnerConjunction {
+                        disjunction: 1,
+                        conjunction: 0
+                    },
+                ]
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert!(matches!(
+            IrmaRequest::from_json(include_str!("../tests/fixtures/requests/truncated.json")),
+            Err(RequestLoadError::Syntax(_))
+        ));
+        assert!(matches!(
+            IrmaRequest::from_json_file("does/not/exist.json"),
+            Err(RequestLoadError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_combinators() {
+        let request = DisclosureRequestBuilder::new()
+            .add_discons(all_of([
+                "irma-demo.MijnOverheid.ageLimits.over18",
+                "irma-demo.MijnOverheid.fullName.firstname",
+            ]))
+            .add_discon(any_of([
+                "pbdf.sidn-pbdf.email.email",
+                "pbdf.pbdf.mobilenumber.mobilenumber",
+            ]))
+            .add_discon(one_of_sets([
+                [
+                    "irma-demo.MijnOverheid.fullName.firstname",
+                    "irma-demo.MijnOverheid.fullName.familyname",
+                ],
+                [
+                    "pbdf.gemeente.personalData.firstnames",
+                    "pbdf.gemeente.personalData.familyname",
+                ],
+            ]))
+            .build();
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&request.to_json().unwrap()).unwrap(),
+            serde_json::json!({
+                "@context": "https://irma.app/ld/request/disclosure/v2",
+                "disclose": [
+                    [
+                        [ "irma-demo.MijnOverheid.ageLimits.over18" ]
+                    ],
+                    [
+                        [ "irma-demo.MijnOverheid.fullName.firstname" ]
+                    ],
+                    [
+                        [ "pbdf.sidn-pbdf.email.email" ],
+                        [ "pbdf.pbdf.mobilenumber.mobilenumber" ]
+                    ],
+                    [
+                        [
+                            "irma-demo.MijnOverheid.fullName.firstname",
+                            "irma-demo.MijnOverheid.fullName.familyname"
+                        ],
+                        [
+                            "pbdf.gemeente.personalData.firstnames",
+                            "pbdf.gemeente.personalData.familyname"
+                        ]
+                    ]
+                ]
+            })
+        );
+
+        assert!(all_of(Vec::<AttributeRequest>::new()).is_empty());
+        assert_eq!(
+            any_of([AttributeRequest::non_null("a.b.c.d")]),
+            vec![vec![AttributeRequest::non_null("a.b.c.d")]]
+        );
+        assert_eq!(
+            one_of_sets([vec![("a.b.c.d", "yes")]]),
+            vec![vec![AttributeRequest::with_value("a.b.c.d", "yes")]]
+        );
+    }
+
+    #[test]
+    fn test_set_label() {
+        let label = |text: &str| TranslatedString {
+            en: text.into(),
+            nl: text.into(),
+        };
+
+        let builder = DisclosureRequestBuilder::new()
+            .add_discons(vec![
+                vec![vec![AttributeRequest::Simple("a.b.c.d".into())]],
+                vec![vec![AttributeRequest::Simple("e.f.g.h".into())]],
+            ])
+            .set_label(0, label("first"))
+            .set_label(1, label("second"))
+            .set_label(1, label("replaced"));
+        assert_eq!(builder.labels().len(), 2);
+        assert_eq!(builder.labels()[&1], label("replaced"));
+        let req = builder.clear_label(0).build();
+        assert_eq!("{\"@context\":\"https://irma.app/ld/request/disclosure/v2\",\"disclose\":[[[\"a.b.c.d\"]],[[\"e.f.g.h\"]]],\"labels\":{\"1\":{\"en\":\"replaced\",\"nl\":\"replaced\"}}}", serde_json::to_string(&req).unwrap());
+
+        assert_eq!(
+            DisclosureRequestBuilder::new()
+                .add_attribute("a.b.c.d")
+                .set_label(1, label("missing"))
+                .try_build(),
+            Err(RequestValidationError::LabelOutOfRange {
+                index: 1,
+                disjunctions: 1
+            })
+        );
+        assert_eq!(
+            SignatureRequestBuilder::new("testmessage".into())
+                .add_attribute("a.b.c.d")
+                .set_label(3, label("missing"))
+                .try_build(),
+            Err(RequestValidationError::LabelOutOfRange {
+                index: 3,
+                disjunctions: 1
+            })
+        );
+        assert_eq!(
+            IssuanceRequestBuilder::new()
+                .add_credential(CredentialBuilder::new("a.b.c".into()).build())
+                .set_label(0, label("missing"))
+                .try_build(),
+            Err(RequestValidationError::LabelOutOfRange {
+                index: 0,
+                disjunctions: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_disclosure_request_validation() {
+        assert_eq!(
+            DisclosureRequestBuilder::new().try_build(),
+            Err(RequestValidationError::NoDisjunctions)
+        );
+        assert_eq!(
+            DisclosureRequestBuilder::new()
+                .add_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]])
+                .add_discon(vec![])
+                .try_build(),
+            Err(RequestValidationError::EmptyDisjunction { disjunction: 1 })
+        );
+        assert_eq!(
+            DisclosureRequestBuilder::new()
+                .add_discon(vec![
+                    vec![],
+                    vec![AttributeRequest::Simple("a.b.c.d".into())]
+                ])
+                .try_build(),
+            Err(RequestValidationError::EmptyInnerConjunction {
+                disjunction: 0,
+                conjunction: 0
+            })
+        );
+        assert!(DisclosureRequestBuilder::new()
+            .add_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]])
+            .try_build()
+            .is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid disclosure request")]
+    fn test_disclosure_request_build_panics() {
+        DisclosureRequestBuilder::new().build();
+    }
+
+    #[test]
+    fn test_signature_request_validation() {
+        assert_eq!(
+            SignatureRequestBuilder::new("testmessage".into()).try_build(),
+            Err(RequestValidationError::NoDisjunctions)
+        );
+        assert_eq!(
+            SignatureRequestBuilder::new("testmessage".into())
+                .add_discon(vec![])
+                .try_build(),
+            Err(RequestValidationError::EmptyDisjunction { disjunction: 0 })
+        );
+        assert_eq!(
+            SignatureRequestBuilder::new("testmessage".into())
+                .add_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]])
+                .add_discon(vec![vec![]])
+                .try_build(),
+            Err(RequestValidationError::EmptyInnerConjunction {
+                disjunction: 1,
+                conjunction: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_issuance_request_validation() {
+        let credential = CredentialBuilder::new("a.b.c".into())
+            .attribute("d".into(), "e".into())
+            .build();
+        assert_eq!(
+            IssuanceRequestBuilder::new()
+                .add_discon(vec![vec![AttributeRequest::Simple("x.y.z.w".into())]])
+                .try_build(),
+            Err(RequestValidationError::NoCredentials)
+        );
+        assert_eq!(
+            IssuanceRequestBuilder::new()
+                .add_credential(credential.clone())
+                .add_discon(vec![])
+                .try_build(),
+            Err(RequestValidationError::EmptyDisjunction { disjunction: 0 })
+        );
+        assert_eq!(
+            IssuanceRequestBuilder::new()
+                .add_credential(credential.clone())
+                .add_discon(vec![vec![]])
+                .try_build(),
+            Err(RequestValidationError::EmptyInnerConjunction {
+                disjunction: 0,
+                conjunction: 0
+            })
+        );
+        // Issuance requests need not ask for attributes
+        assert!(IssuanceRequestBuilder::new()
+            .add_credential(credential)
+            .try_build()
+            .is_ok());
+        assert_eq!(
+            RequestValidationError::EmptyInnerConjunction {
+                disjunction: 0,
+                conjunction: 1
+            }
+            .to_string(),
+            "Option 1 of disjunction 0 of the request does not contain any attributes"
+        );
+    }
+
+    #[test]
+    fn test_add_attribute() {
+        let label = || TranslatedString {
+            en: "en".into(),
+            nl: "nl".into(),
+        };
+
+        let disclosure = DisclosureRequestBuilder::new()
+            .add_attribute(AttributeRequest::non_null("x.y.z.w"))
+            .add_attribute_with_label(AttributeRequest::Simple("a.b.c.d".into()), label())
+            .build();
+        assert_eq!("{\"@context\":\"https://irma.app/ld/request/disclosure/v2\",\"disclose\":[[[{\"type\":\"x.y.z.w\",\"notNull\":true}]],[[\"a.b.c.d\"]]],\"labels\":{\"1\":{\"en\":\"en\",\"nl\":\"nl\"}}}", serde_json::to_string(&disclosure).unwrap());
+        assert_eq!(
+            disclosure,
+            DisclosureRequestBuilder::new()
+                .add_discon(vec![vec![AttributeRequest::non_null("x.y.z.w")]])
+                .add_discon_with_label(
+                    vec![vec![AttributeRequest::Simple("a.b.c.d".into())]],
+                    label()
+                )
+                .build()
+        );
+
+        let signature = SignatureRequestBuilder::new("testmessage".into())
+            .add_attribute_with_label(AttributeRequest::Simple("a.b.c.d".into()), label())
+            .build();
+        assert_eq!("{\"@context\":\"https://irma.app/ld/request/signature/v2\",\"message\":\"testmessage\",\"disclose\":[[[\"a.b.c.d\"]]],\"labels\":{\"0\":{\"en\":\"en\",\"nl\":\"nl\"}}}", serde_json::to_string(&signature).unwrap());
+
+        let issuance = IssuanceRequestBuilder::new()
+            .add_attribute(AttributeRequest::Simple("x.y.z.w".into()))
+            .add_credential(Credential {
+                credential: "a.b.c".parse().unwrap(),
+                validity: Some(123456789),
+                attributes: hashmap![
+                    "d".into() => "e".into(),
+                ],
+                revocation_key: None,
+            })
+            .build();
+        assert_eq!("{\"@context\":\"https://irma.app/ld/request/issuance/v2\",\"credentials\":[{\"credential\":\"a.b.c\",\"validity\":123456789,\"attributes\":{\"d\":\"e\"}}],\"disclose\":[[[\"x.y.z.w\"]]]}", serde_json::to_string(&issuance).unwrap());
+    }
+
+    #[test]
+    fn test_add_choice() {
+        let email = || AttributeRequest::Simple("pbdf.sidn-pbdf.email.email".into());
+        let phone = || AttributeRequest::Simple("pbdf.sidn-pbdf.mobilenumber.mobilenumber".into());
+        let verified = || AttributeRequest::with_value("irma-demo.app.account.verified", "yes");
+        let choice = || {
+            Choice::new()
+                .option([email()])
+                .option([phone(), verified()])
+        };
+
+        let req = DisclosureRequestBuilder::new().add_choice(choice()).build();
+        assert_eq!(
+            serde_json::to_value(&req).unwrap(),
+            serde_json::json!({
+                "@context": "https://irma.app/ld/request/disclosure/v2",
+                "disclose": [
+                    [
+                        ["pbdf.sidn-pbdf.email.email"],
+                        [
+                            "pbdf.sidn-pbdf.mobilenumber.mobilenumber",
+                            {"type": "irma-demo.app.account.verified", "value": "yes"}
+                        ]
+                    ]
+                ]
+            })
+        );
+        assert_eq!(
+            req,
+            DisclosureRequestBuilder::new()
+                .add_discon(vec![vec![email()], vec![phone(), verified()]])
+                .build()
+        );
+
+        let req = SignatureRequestBuilder::new("testmessage".into())
+            .add_choice(Choice::of(email()))
+            .build();
+        assert_eq!(
+            req,
+            SignatureRequestBuilder::new("testmessage".into())
+                .add_attribute(email())
+                .build()
+        );
+
+        let req = IssuanceRequestBuilder::new()
+            .add_credential(
+                CredentialBuilder::new("a.b.c".into())
+                    .attribute("d".into(), "e".into())
+                    .build(),
+            )
+            .add_choice(choice())
+            .build();
+        assert_eq!(
+            serde_json::to_value(&req).unwrap()["disclose"],
+            serde_json::json!([[
+                ["pbdf.sidn-pbdf.email.email"],
+                [
+                    "pbdf.sidn-pbdf.mobilenumber.mobilenumber",
+                    {"type": "irma-demo.app.account.verified", "value": "yes"}
+                ]
+            ]])
+        );
+    }
 }
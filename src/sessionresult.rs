@@ -1,4 +1,12 @@
-use crate::{irmaclient::SessionToken, util::TranslatedString};
+use crate::{
+    irmaclient::SessionToken, util::TranslatedString, Error, FreshnessViolation, IrmaSignature,
+    ResultError,
+};
+
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -35,6 +43,27 @@ pub enum SessionStatus {
     Timeout,
 }
 
+impl SessionStatus {
+    /// Whether the session can no longer change status.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            SessionStatus::Cancelled | SessionStatus::Done | SessionStatus::Timeout
+        )
+    }
+
+    // Position of the status in the normal lifecycle of a session, all terminal states being last.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn progress(&self) -> u8 {
+        match self {
+            SessionStatus::Initialized => 0,
+            SessionStatus::Pairing => 1,
+            SessionStatus::Connected => 2,
+            SessionStatus::Cancelled | SessionStatus::Done | SessionStatus::Timeout => 3,
+        }
+    }
+}
+
 /// Type of an IRMA session
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -45,8 +74,7 @@ pub enum SessionType {
 }
 
 /// A disclosed attribute
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct DisclosedAttribute {
     /// The value of the attribute as encoded in the credential
     #[serde(rename = "rawvalue", skip_serializing_if = "Option::is_none")]
@@ -59,11 +87,100 @@ pub struct DisclosedAttribute {
     pub identifier: String,
     /// Additional information on the role of the disclosed attribute in the complete session result
     pub status: AttributeStatus,
+    /// Unix timestamp of when the credential containing the attribute was issued. The IRMA app
+    /// reports this rounded down to a multiple of a week.
+    #[serde(
+        rename = "issuancetime",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub issuance_time: Option<u64>,
+    /// Whether the credential containing the attribute was proven not to be revoked. Only present
+    /// when the request asked for a proof of non-revocation for its credential type.
+    #[serde(
+        rename = "notrevoked",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub not_revoked: Option<bool>,
+    /// Unix timestamp up to which the non-revocation proof holds, present when the proof was made
+    /// against revocation information that the irma server knows to be outdated. Absent when the
+    /// proof holds up to the moment of the session.
+    #[serde(
+        rename = "notrevokedbefore",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub not_revoked_before: Option<u64>,
+}
+
+impl DisclosedAttribute {
+    /// When the credential containing the attribute was issued, if known
+    pub fn issued_at(&self) -> Option<SystemTime> {
+        self.issuance_time
+            .map(|time| SystemTime::UNIX_EPOCH + Duration::from_secs(time))
+    }
+
+    /// Whether the credential containing the attribute was issued at most the given duration
+    /// before now. None when the issuance time is unknown. Credentials issued after now count as
+    /// fresh, so small clock differences with the IRMA app do not matter.
+    pub fn issued_within(&self, max_age: Duration, now: SystemTime) -> Option<bool> {
+        let issued = self.issued_at()?;
+        Some(
+            now.duration_since(issued)
+                .map_or(true, |age| age <= max_age),
+        )
+    }
+
+    /// Whether the credential containing the attribute is proven not to have been revoked at the
+    /// given time, which should not lie after the session. None when the result contains no proof
+    /// of non-revocation for the attribute.
+    pub fn is_not_revoked_as_of(&self, time: SystemTime) -> Option<bool> {
+        match (self.not_revoked?, self.not_revoked_before) {
+            (false, _) => Some(false),
+            (true, None) => Some(true),
+            (true, Some(before)) => {
+                Some(time < SystemTime::UNIX_EPOCH + Duration::from_secs(before))
+            }
+        }
+    }
+}
+
+/// Requirements on the credentials of the attributes disclosed in a session, checked with
+/// [SessionResult::check_freshness].
+///
+/// Expiry is not part of the disclosed attributes: when one of the credentials has expired, the
+/// irma server reports a proof status of [ProofStatus::Expired] for the whole session. That status
+/// violates the policy unless `allow_expired` is set. When the request used `skip_expiry_check`,
+/// the irma server accepts expired credentials with a [ProofStatus::Valid], so the maximum age is
+/// then the only guard against old credentials.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FreshnessPolicy {
+    /// Maximum time between the issuance of a credential and now
+    pub max_age: Duration,
+    /// Whether attributes of which the issuance time is unknown violate the policy. Defaults to
+    /// true.
+    pub require_issuance_time: bool,
+    /// Whether a proof status of [ProofStatus::Expired] is accepted. Defaults to false.
+    pub allow_expired: bool,
+    /// Moment at which the age of credentials is determined, or None for the current time
+    pub now: Option<SystemTime>,
+}
+
+impl FreshnessPolicy {
+    /// Policy accepting credentials issued at most `max_age` ago
+    pub fn new(max_age: Duration) -> FreshnessPolicy {
+        FreshnessPolicy {
+            max_age,
+            require_issuance_time: true,
+            allow_expired: false,
+            now: None,
+        }
+    }
 }
 
 /// Results of a session
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SessionResult {
     /// Token of the session
     pub token: SessionToken,
@@ -78,16 +195,308 @@ pub struct SessionResult {
     /// Attributes disclosed by the irma client to the server
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub disclosed: Vec<Vec<DisclosedAttribute>>,
-    /// The full signature, if this was a signing session, as parsed json.
+    /// The full signature, if this was a signing session, see [IrmaSignature] for how signatures
+    /// compare
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub signature: Option<serde_json::Value>,
+    pub signature: Option<IrmaSignature>,
+    /// Token of the session chained to this one, if any
+    #[serde(
+        rename = "nextSession",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub next_session: Option<SessionToken>,
+}
+
+impl SessionResult {
+    /// The first disclosed attribute with exactly the given identifier, searching all disjunctions
+    /// in order.
+    ///
+    /// Attributes that the user did not disclose, for example in an optional disjunction, are
+    /// included with status [AttributeStatus::Null] and have no `raw_value`, so check the status
+    /// before relying on the value.
+    pub fn attribute(&self, id: &str) -> Option<&DisclosedAttribute> {
+        self.attributes(id).next()
+    }
+
+    /// All disclosed attributes with exactly the given identifier, in order, for requests in which
+    /// the same attribute can be disclosed more than once. As with [SessionResult::attribute],
+    /// attributes with status [AttributeStatus::Null] have no `raw_value`.
+    pub fn attributes<'a, 'b>(
+        &'a self,
+        id: &'b str,
+    ) -> impl Iterator<Item = &'a DisclosedAttribute> + 'b
+    where
+        'a: 'b,
+    {
+        self.disclosed
+            .iter()
+            .flatten()
+            .filter(move |attribute| attribute.identifier == id)
+    }
+
+    /// The raw value of the first attribute with the given identifier and status
+    /// [AttributeStatus::Present], for attributes that the application cannot do without. Fails
+    /// unless the session is done and its proof is valid. Attributes with status
+    /// [AttributeStatus::Extra] were not requested and do not count as disclosed.
+    pub fn require_attribute(&self, id: &str) -> Result<&str, ResultError> {
+        if self.status != SessionStatus::Done || self.proof_status != Some(ProofStatus::Valid) {
+            return Err(ResultError::SessionNotValid {
+                identifier: id.into(),
+                status: self.status.clone(),
+                proof_status: self.proof_status.clone(),
+            });
+        }
+        let mut without_value = false;
+        for attribute in self.attributes(id) {
+            match (&attribute.status, &attribute.raw_value) {
+                (AttributeStatus::Present, Some(value)) => return Ok(value),
+                (AttributeStatus::Present | AttributeStatus::Null, None) => without_value = true,
+                _ => {}
+            }
+        }
+        if without_value {
+            Err(ResultError::NullValue(id.into()))
+        } else {
+            Err(ResultError::NotDisclosed(id.into()))
+        }
+    }
+
+    /// The raw values of all disclosed attributes by identifier, None for attributes with status
+    /// [AttributeStatus::Null]. When an identifier occurs more than once, its first occurrence
+    /// wins, as with [SessionResult::attribute].
+    pub fn disclosed_values(&self) -> HashMap<String, Option<String>> {
+        let mut values = HashMap::new();
+        for attribute in self.disclosed.iter().flatten() {
+            values
+                .entry(attribute.identifier.clone())
+                .or_insert_with(|| match attribute.status {
+                    AttributeStatus::Null => None,
+                    _ => attribute.raw_value.clone(),
+                });
+        }
+        values
+    }
+
+    /// The raw values of the attributes with status [AttributeStatus::Present] by identifier,
+    /// leaving out attributes that were not disclosed ([AttributeStatus::Null]) or not requested
+    /// ([AttributeStatus::Extra]). When an identifier occurs more than once, its first present
+    /// occurrence wins.
+    pub fn disclosed_values_present(&self) -> HashMap<String, String> {
+        let mut values = HashMap::new();
+        for attribute in self.disclosed.iter().flatten() {
+            if let (AttributeStatus::Present, Some(value)) =
+                (&attribute.status, &attribute.raw_value)
+            {
+                values
+                    .entry(attribute.identifier.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+        values
+    }
+
+    /// Check that the disclosed attributes satisfy the freshness policy, reporting the first
+    /// violation. Attributes that the user did not disclose (with status
+    /// [AttributeStatus::Null]) are skipped.
+    pub fn check_freshness(&self, policy: &FreshnessPolicy) -> Result<(), FreshnessViolation> {
+        if self.proof_status == Some(ProofStatus::Expired) && !policy.allow_expired {
+            return Err(FreshnessViolation::Expired);
+        }
+        let now = policy.now.unwrap_or_else(SystemTime::now);
+        for attribute in self.disclosed.iter().flatten() {
+            if attribute.status == AttributeStatus::Null {
+                continue;
+            }
+            match attribute.issued_at() {
+                Some(issued) => {
+                    let age = now.duration_since(issued).unwrap_or_default();
+                    if age > policy.max_age {
+                        return Err(FreshnessViolation::TooOld {
+                            identifier: attribute.identifier.clone(),
+                            age,
+                            max_age: policy.max_age,
+                        });
+                    }
+                }
+                None if policy.require_issuance_time => {
+                    return Err(FreshnessViolation::UnknownIssuanceTime(
+                        attribute.identifier.clone(),
+                    ))
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The message signed in a signature session, if any
+    pub fn signed_message(&self) -> Option<&str> {
+        self.signature.as_ref().map(IrmaSignature::message)
+    }
+
+    /// The signature of a signing session exactly as received from the irma server, for archival
+    /// or for verification with irmago or the `irma` command line tool
+    pub fn signature_json(&self) -> Option<String> {
+        self.signature
+            .as_ref()
+            .map(|signature| signature.raw().get().to_owned())
+    }
+
+    /// Whether the signed message equals the canonical JSON form of the candidate document, as
+    /// produced by [SignatureRequestBuilder::new_json](crate::SignatureRequestBuilder::new_json).
+    /// Returns false when the result contains no signature.
+    pub fn signs_json<T: Serialize + ?Sized>(&self, candidate: &T) -> Result<bool, Error> {
+        let candidate = crate::canonical_json(candidate)?;
+        Ok(self.signed_message() == Some(candidate.as_str()))
+    }
+}
+
+/// Claims of a session result JWT, as signed by the irma server. These contain the session result
+/// next to the standard JWT claims.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionResultJwtClaims {
+    /// Issuer of the JWT, the name of the irma server (by default `irmaserver`)
+    pub iss: String,
+    /// Unix timestamp of when the JWT was issued
+    pub iat: u64,
+    /// Unix timestamp after which the JWT is no longer valid, if any
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub exp: Option<u64>,
+    /// Subject of the JWT, indicating the type of the session (e.g. `disclosing_result`)
+    pub sub: String,
+    /// Token of the session
+    pub token: SessionToken,
+    /// Type of the session, which older irma servers only include in the subject
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none", default)]
+    pub sessiontype: Option<SessionType>,
+    /// State of the session
+    pub status: SessionStatus,
+    /// Status of the proof provided by the irma client
+    #[serde(
+        rename = "proofStatus",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub proof_status: Option<ProofStatus>,
+    /// Attributes disclosed by the irma client to the server
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        default,
+        deserialize_with = "de_disclosed"
+    )]
+    pub disclosed: Vec<Vec<DisclosedAttribute>>,
+    /// The full signature, if this was a signing session, see [IrmaSignature] for how signatures
+    /// compare
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub signature: Option<IrmaSignature>,
+    /// Token of the session chained to this one, if any
+    #[serde(
+        rename = "nextSession",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub next_session: Option<SessionToken>,
+}
+
+impl SessionResultJwtClaims {
+    /// Type of the session, taken from the subject when not included separately. Unknown
+    /// subjects are assumed to be disclosure results.
+    pub fn sessiontype(&self) -> SessionType {
+        if let Some(sessiontype) = &self.sessiontype {
+            return sessiontype.clone();
+        }
+        match self.sub.as_str() {
+            "signing_result" => SessionType::Signing,
+            "issuing_result" => SessionType::Issuing,
+            _ => SessionType::Disclosing,
+        }
+    }
+}
+
+impl From<SessionResultJwtClaims> for SessionResult {
+    fn from(claims: SessionResultJwtClaims) -> Self {
+        SessionResult {
+            sessiontype: claims.sessiontype(),
+            token: claims.token,
+            status: claims.status,
+            proof_status: claims.proof_status,
+            disclosed: claims.disclosed,
+            signature: claims.signature,
+            next_session: claims.next_session,
+        }
+    }
+}
+
+/// Claims of the JWT returned by the getproof endpoint (see [crate::IrmaClient::get_proof]),
+/// containing the full proof of a finished session for archival. The proof itself is kept exactly
+/// as received, as verifiers of the proof can be sensitive to its precise encoding.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProofJwtClaims {
+    /// Issuer of the JWT, the name of the irma server (by default `irmaserver`)
+    pub iss: String,
+    /// Unix timestamp of when the JWT was issued
+    pub iat: u64,
+    /// Subject of the JWT, indicating the type of the session (e.g. `disclosing_result`)
+    pub sub: String,
+    /// State of the session
+    pub status: SessionStatus,
+    /// Type of the session, if included
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none", default)]
+    pub sessiontype: Option<SessionType>,
+    /// Attributes disclosed in the proof, if included
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        default,
+        deserialize_with = "de_disclosed"
+    )]
+    pub disclosed: Vec<Vec<DisclosedAttribute>>,
+    /// The proof, as the exact json text contained in the JWT
+    pub proof: Box<serde_json::value::RawValue>,
+}
+
+impl ProofJwtClaims {
+    /// The json text of the proof, byte for byte as contained in the JWT, without any reordering
+    /// of fields or reformatting of numbers
+    pub fn raw_proof(&self) -> &str {
+        self.proof.get()
+    }
+}
+
+// Disclosed attributes in result JWTs are either grouped per disjunction, as in the REST result,
+// or a flat list (for requests without disjunctions), in which case every attribute becomes its
+// own group.
+fn de_disclosed<'de, D>(deserializer: D) -> Result<Vec<Vec<DisclosedAttribute>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Disclosed {
+        Grouped(Vec<Vec<DisclosedAttribute>>),
+        Flat(Vec<DisclosedAttribute>),
+    }
+
+    Ok(match Option::<Disclosed>::deserialize(deserializer)? {
+        Some(Disclosed::Grouped(disclosed)) => disclosed,
+        Some(Disclosed::Flat(disclosed)) => disclosed
+            .into_iter()
+            .map(|attribute| vec![attribute])
+            .collect(),
+        None => vec![],
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use maplit::hashmap;
+
     use crate::{
-        AttributeStatus, DisclosedAttribute, ProofStatus, SessionResult, SessionStatus,
-        SessionToken, SessionType, TranslatedString,
+        signature::AttributeIndex, AttributeStatus, DisclosedAttribute, FreshnessPolicy,
+        FreshnessViolation, IrmaSignature, ProofJwtClaims, ProofStatus, ResultError, SessionResult,
+        SessionResultJwtClaims, SessionStatus, SessionToken, SessionType, TranslatedString,
     };
 
     #[test]
@@ -127,10 +536,14 @@ mod tests {
                     en: "yes".into(),
                     nl: "yes".into(),
                 }),
+                issuance_time: None,
+                not_revoked: None,
+                not_revoked_before: None,
             }]],
             proof_status: Some(ProofStatus::Valid),
             token: SessionToken("ELMExi5iauWYHzbH7gwU".into()),
             signature: None,
+            next_session: None,
         };
 
         assert_eq!(result, expected);
@@ -157,6 +570,7 @@ mod tests {
             proof_status: None,
             token: SessionToken("ELMExi5iauWYHzbH7gwU".into()),
             signature: None,
+            next_session: None,
         };
 
         assert_eq!(result, expected);
@@ -184,6 +598,7 @@ mod tests {
             proof_status: Some(ProofStatus::Valid),
             token: SessionToken("bVqg9btHRhiMvEWs8axQ".into()),
             signature: None,
+            next_session: None,
         };
 
         assert_eq!(result, expected);
@@ -192,8 +607,7 @@ mod tests {
             serde_json::from_str(&serde_json::to_string(&expected).unwrap()).unwrap()
         );
 
-        let mut result = serde_json::from_str::<SessionResult>(
-            r#"
+        let json = r#"
             {
                 "token": "5bTpPRXctenYGGsZVe3x",
                 "status": "DONE",
@@ -258,9 +672,8 @@ mod tests {
                     }
                 }
             }
-        "#,
-        )
-        .unwrap();
+        "#;
+        let mut result = serde_json::from_str::<SessionResult>(json).unwrap();
 
         let expected = SessionResult {
             sessiontype: SessionType::Signing,
@@ -273,15 +686,60 @@ mod tests {
                     nl: "Testtown".into(),
                 }),
                 identifier: "irma-demo.gemeente.address.city".into(),
+                issuance_time: Some(1632355200),
+                not_revoked: None,
+                not_revoked_before: None,
             }]],
             proof_status: Some(ProofStatus::Valid),
             token: SessionToken("5bTpPRXctenYGGsZVe3x".into()),
             signature: None,
+            next_session: None,
         };
 
-        // Ignore signature as we are not fully parsing that
-        assert!(result.signature.is_some());
-        result.signature = None;
+        assert_eq!(
+            result.disclosed[0][0].issued_at(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1632355200))
+        );
+
+        let signature = result.signature.take().unwrap();
+        assert_eq!(
+            signature.ld_context(),
+            Some("https://irma.app/ld/signature/v2")
+        );
+        assert_eq!(signature.message(), "Test message");
+        assert_eq!(signature.nonce(), Some("mO6XOXwetC2zIQ1jQPyKzQ=="));
+        assert_eq!(signature.context(), Some("AQ=="));
+        assert_eq!(
+            signature.indices(),
+            [vec![AttributeIndex { cred: 0, attr: 6 }]]
+        );
+        let timestamp = signature.timestamp().unwrap();
+        assert_eq!(timestamp.time, 1636103859);
+        assert_eq!(
+            timestamp.server_url,
+            "https://keyshare.privacybydesign.foundation/atumd/"
+        );
+        assert_eq!(timestamp.sig.alg, "ed25519");
+        assert_eq!(
+            timestamp.sig.public_key,
+            "MKdXxJxEWPRIwNP7SuvP0J/M/NV51VZvqCyO+7eDwJ8="
+        );
+        assert!(signature
+            .proofs()
+            .unwrap()
+            .get()
+            .contains(r#""e_response": "rYPv4DSPe+/T85gymucls7UPYP9cvq15DGhcGJWcJxiCqzCo6WX0D0Z7rjvDBVGHZe/fBu9l+vty""#));
+        assert_eq!(
+            signature.to_value(),
+            serde_json::from_str::<serde_json::Value>(json).unwrap()["signature"]
+        );
+
+        // The signature serializes to exactly the JSON received
+        let serialized = serde_json::to_string(&signature).unwrap();
+        assert!(json.contains(&serialized));
+        let reparsed: IrmaSignature = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(reparsed.raw().get(), signature.raw().get());
+        assert_eq!(reparsed, signature);
 
         assert_eq!(result, expected);
         assert_eq!(
@@ -289,4 +747,501 @@ mod tests {
             serde_json::from_str(&serde_json::to_string(&expected).unwrap()).unwrap()
         );
     }
+
+    #[test]
+    fn test_decode_result_jwt_claims() {
+        // Payload of a result JWT from irmago
+        let claims = serde_json::from_str::<SessionResultJwtClaims>(
+            r#"
+            {
+                "iss": "irmaserver",
+                "iat": 1632393090,
+                "exp": 1632393210,
+                "sub": "disclosing_result",
+                "token": "ELMExi5iauWYHzbH7gwU",
+                "status": "DONE",
+                "type": "disclosing",
+                "proofStatus": "VALID",
+                "disclosed": [
+                    [
+                        {
+                            "rawvalue": "yes",
+                            "value": {
+                                "": "yes",
+                                "en": "yes",
+                                "nl": "yes"
+                            },
+                            "id": "irma-demo.MijnOverheid.ageLower.over18",
+                            "status": "PRESENT",
+                            "issuancetime": 1632355200
+                        }
+                    ]
+                ]
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(claims.iss, "irmaserver");
+        assert_eq!(claims.iat, 1632393090);
+        assert_eq!(claims.exp, Some(1632393210));
+
+        let disclosed = vec![vec![DisclosedAttribute {
+            status: AttributeStatus::Present,
+            raw_value: Some("yes".into()),
+            identifier: "irma-demo.MijnOverheid.ageLower.over18".into(),
+            value: Some(TranslatedString {
+                en: "yes".into(),
+                nl: "yes".into(),
+            }),
+            issuance_time: Some(1632355200),
+            not_revoked: None,
+            not_revoked_before: None,
+        }]];
+        let expected = SessionResult {
+            sessiontype: SessionType::Disclosing,
+            status: SessionStatus::Done,
+            disclosed: disclosed.clone(),
+            proof_status: Some(ProofStatus::Valid),
+            token: SessionToken("ELMExi5iauWYHzbH7gwU".into()),
+            signature: None,
+            next_session: None,
+        };
+        assert_eq!(SessionResult::from(claims.clone()), expected);
+        assert_eq!(
+            claims,
+            serde_json::from_str(&serde_json::to_string(&claims).unwrap()).unwrap()
+        );
+
+        // Without separate type, and with an ungrouped list of disclosed attributes
+        let claims = serde_json::from_str::<SessionResultJwtClaims>(
+            r#"
+            {
+                "iss": "irmaserver",
+                "iat": 1632393090,
+                "sub": "signing_result",
+                "token": "5bTpPRXctenYGGsZVe3x",
+                "status": "DONE",
+                "proofStatus": "VALID",
+                "disclosed": [
+                    {
+                        "rawvalue": "yes",
+                        "value": {
+                            "": "yes",
+                            "en": "yes",
+                            "nl": "yes"
+                        },
+                        "id": "irma-demo.MijnOverheid.ageLower.over18",
+                        "status": "PRESENT",
+                        "issuancetime": 1632355200
+                    }
+                ],
+                "signature": {
+                    "@context": "https://irma.app/ld/signature/v2",
+                    "message": "message"
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(claims.exp, None);
+        let result = SessionResult::from(claims);
+        assert_eq!(result.sessiontype, SessionType::Signing);
+        assert_eq!(result.disclosed, disclosed);
+        assert_eq!(result.signature.unwrap().message(), "message");
+    }
+
+    #[test]
+    fn test_proof_jwt_claims_roundtrip() {
+        let payload = include_str!("../tests/fixtures/proof_claims.json").trim();
+        let claims: ProofJwtClaims = serde_json::from_str(payload).unwrap();
+        assert_eq!(claims.iss, "irmaserver");
+        assert_eq!(claims.iat, 1632393090);
+        assert_eq!(claims.status, SessionStatus::Done);
+        assert_eq!(claims.sessiontype, Some(SessionType::Disclosing));
+        assert_eq!(
+            claims.disclosed[0][0].identifier,
+            "irma-demo.MijnOverheid.ageLower.over18"
+        );
+
+        // The proof is preserved byte for byte, including the big integers and float notation
+        // that a round trip through serde_json::Value would change
+        let start = payload.find(r#"{"@context""#).unwrap();
+        let expected = &payload[start..payload.len() - 1];
+        assert_eq!(claims.raw_proof(), expected);
+        assert!(claims.raw_proof().contains(r#""e_response":1.0e-3"#));
+        let serialized = serde_json::to_string(&claims).unwrap();
+        assert!(serialized.ends_with(&format!(r#""proof":{}}}"#, expected)));
+
+        // Without disclosed attributes or session type
+        let claims: ProofJwtClaims = serde_json::from_str(
+            r#"{"iss":"irmaserver","iat":1,"sub":"signing_result","status":"DONE","proof":{ "a" : [1, 2.50] }}"#,
+        )
+        .unwrap();
+        assert!(claims.disclosed.is_empty());
+        assert_eq!(claims.raw_proof(), r#"{ "a" : [1, 2.50] }"#);
+    }
+
+    #[test]
+    fn test_decode_result_optional_disjunction() {
+        // The user chose not to disclose anything for the optional second disjunction
+        let disclosed = r#"
+            [
+                [{
+                    "rawvalue": "yes",
+                    "id": "irma-demo.MijnOverheid.ageLower.over18",
+                    "status": "PRESENT"
+                }],
+                []
+            ]"#;
+        let result = serde_json::from_str::<SessionResult>(&format!(
+            r#"{{"type": "disclosing", "status": "DONE", "proofStatus": "VALID", "token": "ELMExi5iauWYHzbH7gwU", "disclosed": {}}}"#,
+            disclosed
+        ))
+        .unwrap();
+        assert_eq!(result.disclosed.len(), 2);
+        assert_eq!(result.disclosed[0].len(), 1);
+        assert!(result.disclosed[1].is_empty());
+
+        let claims = serde_json::from_str::<SessionResultJwtClaims>(&format!(
+            r#"{{"iss": "irmaserver", "iat": 1632393090, "sub": "disclosing_result", "token": "ELMExi5iauWYHzbH7gwU", "status": "DONE", "disclosed": {}}}"#,
+            disclosed
+        ))
+        .unwrap();
+        assert_eq!(claims.disclosed, result.disclosed);
+    }
+
+    #[test]
+    fn test_decode_result_revocation() {
+        // Synthetic result written by hand to cover each combination of the revocation fields,
+        // not output captured from an irma server
+        let result = serde_json::from_str::<SessionResult>(include_str!(
+            "../tests/fixtures/revocation_result.json"
+        ))
+        .unwrap();
+        let at = |time| SystemTime::UNIX_EPOCH + Duration::from_secs(time);
+
+        let current = &result.disclosed[0][0];
+        assert_eq!(current.not_revoked, Some(true));
+        assert_eq!(current.not_revoked_before, None);
+        assert_eq!(current.is_not_revoked_as_of(at(1636103859)), Some(true));
+
+        let outdated = &result.disclosed[1][0];
+        assert_eq!(outdated.not_revoked, Some(true));
+        assert_eq!(outdated.not_revoked_before, Some(1636103859));
+        assert_eq!(outdated.is_not_revoked_as_of(at(1636103858)), Some(true));
+        assert_eq!(outdated.is_not_revoked_as_of(at(1636103859)), Some(false));
+
+        let unchecked = &result.disclosed[2][0];
+        assert_eq!(unchecked.not_revoked, None);
+        assert_eq!(unchecked.is_not_revoked_as_of(at(1636103859)), None);
+
+        let revoked = DisclosedAttribute {
+            not_revoked: Some(false),
+            not_revoked_before: None,
+            ..current.clone()
+        };
+        assert_eq!(revoked.is_not_revoked_as_of(at(0)), Some(false));
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["disclosed"][1][0]["notrevoked"], true);
+        assert_eq!(json["disclosed"][1][0]["notrevokedbefore"], 1636103859);
+        assert!(json["disclosed"][2][0].get("notrevoked").is_none());
+        assert_eq!(
+            serde_json::from_value::<SessionResult>(json).unwrap(),
+            result
+        );
+    }
+
+    #[test]
+    fn test_check_freshness() {
+        // Synthetic result written by hand with attributes of various issuance times, not output
+        // captured from an irma server
+        let result = serde_json::from_str::<SessionResult>(include_str!(
+            "../tests/fixtures/freshness_result.json"
+        ))
+        .unwrap();
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1636103859);
+        let year = Duration::from_secs(365 * 24 * 60 * 60);
+
+        let recent = &result.disclosed[0][0];
+        assert_eq!(recent.issued_within(year, now), Some(true));
+        assert_eq!(
+            recent.issued_within(Duration::from_secs(60 * 60 * 24), now),
+            Some(false)
+        );
+        assert_eq!(
+            recent.issued_within(Duration::ZERO, SystemTime::UNIX_EPOCH),
+            Some(true)
+        );
+        assert_eq!(result.disclosed[1][0].issued_within(year, now), None);
+        assert_eq!(result.disclosed[2][0].issued_within(year, now), Some(false));
+
+        let policy = FreshnessPolicy {
+            now: Some(now),
+            ..FreshnessPolicy::new(year)
+        };
+        assert_eq!(
+            result.check_freshness(&policy),
+            Err(FreshnessViolation::TooOld {
+                identifier: "irma-demo.gemeente.address.city".into(),
+                age: Duration::from_secs(1636103859 - 1515369600),
+                max_age: year,
+            })
+        );
+        assert_eq!(
+            result.check_freshness(&FreshnessPolicy {
+                max_age: 5 * year,
+                ..policy.clone()
+            }),
+            Ok(())
+        );
+
+        // Attributes without issuance time are only accepted when the policy allows it
+        let mut unknown = result.clone();
+        unknown.disclosed[2][0].issuance_time = None;
+        assert_eq!(
+            unknown.check_freshness(&policy),
+            Err(FreshnessViolation::UnknownIssuanceTime(
+                "irma-demo.gemeente.address.city".into()
+            ))
+        );
+        assert_eq!(
+            unknown.check_freshness(&FreshnessPolicy {
+                require_issuance_time: false,
+                ..policy.clone()
+            }),
+            Ok(())
+        );
+
+        // Expired credentials are reported by the proof status of the whole session
+        let expired = SessionResult {
+            proof_status: Some(ProofStatus::Expired),
+            ..unknown
+        };
+        let lenient = FreshnessPolicy {
+            require_issuance_time: false,
+            ..policy
+        };
+        assert_eq!(
+            expired.check_freshness(&lenient),
+            Err(FreshnessViolation::Expired)
+        );
+        assert_eq!(
+            expired.check_freshness(&FreshnessPolicy {
+                allow_expired: true,
+                ..lenient
+            }),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_attribute_lookup() {
+        let result = serde_json::from_str::<SessionResult>(include_str!(
+            "../tests/fixtures/disclosure_result.json"
+        ))
+        .unwrap();
+
+        let over18 = result
+            .attribute("irma-demo.MijnOverheid.ageLimits.over18")
+            .unwrap();
+        assert_eq!(over18.raw_value.as_deref(), Some("yes"));
+        assert_eq!(over18.status, AttributeStatus::Present);
+
+        // Found attributes borrow from the result only, not from the identifier
+        let found: Vec<_> = {
+            let id = String::from("irma-demo.MijnOverheid.ageLimits.over18");
+            result.attributes(&id).collect()
+        };
+        assert_eq!(found, [over18]);
+
+        // Attributes in later inner conjunctions are found as well
+        let city = result.attribute("irma-demo.gemeente.address.city").unwrap();
+        assert_eq!(city.raw_value.as_deref(), Some("Testtown"));
+
+        let email = result.attribute("pbdf.sidn-pbdf.email.email").unwrap();
+        assert_eq!(email.status, AttributeStatus::Null);
+        assert_eq!(email.raw_value, None);
+
+        assert!(result
+            .attribute("irma-demo.MijnOverheid.ageLimits.over21")
+            .is_none());
+        // Only exact identifiers match
+        assert!(result
+            .attribute("irma-demo.MijnOverheid.ageLimits")
+            .is_none());
+        assert!(result
+            .attribute("IRMA-DEMO.MijnOverheid.ageLimits.over18")
+            .is_none());
+        assert_eq!(
+            result
+                .attributes("pbdf.pbdf.mobilenumber.mobilenumber")
+                .count(),
+            0
+        );
+
+        let names: Vec<_> = result
+            .attributes("irma-demo.MijnOverheid.fullName.firstname")
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[0].status, AttributeStatus::Present);
+        assert_eq!(names[1].status, AttributeStatus::Extra);
+        assert_eq!(
+            result.attribute("irma-demo.MijnOverheid.fullName.firstname"),
+            Some(names[0])
+        );
+    }
+
+    #[test]
+    fn test_disclosed_values() {
+        let mut result = serde_json::from_str::<SessionResult>(include_str!(
+            "../tests/fixtures/disclosure_result.json"
+        ))
+        .unwrap();
+
+        assert_eq!(
+            result.disclosed_values(),
+            hashmap! {
+                "irma-demo.MijnOverheid.ageLimits.over18".into() => Some("yes".into()),
+                "irma-demo.MijnOverheid.fullName.firstname".into() => Some("Alice".into()),
+                "pbdf.sidn-pbdf.email.email".into() => None,
+                "irma-demo.gemeente.address.city".into() => Some("Testtown".into()),
+            }
+        );
+        assert_eq!(
+            result.disclosed_values_present(),
+            hashmap! {
+                "irma-demo.MijnOverheid.ageLimits.over18".into() => "yes".into(),
+                "irma-demo.MijnOverheid.fullName.firstname".into() => "Alice".into(),
+                "irma-demo.gemeente.address.city".into() => "Testtown".into(),
+            }
+        );
+
+        // The first occurrence of an identifier wins, and extra attributes are never present ones
+        result.disclosed[0][1].raw_value = Some("Bob".into());
+        assert_eq!(
+            result.disclosed_values()["irma-demo.MijnOverheid.fullName.firstname"],
+            Some("Bob".into())
+        );
+        assert_eq!(
+            result.disclosed_values_present()["irma-demo.MijnOverheid.fullName.firstname"],
+            "Bob"
+        );
+        result.disclosed[0][1].status = AttributeStatus::Null;
+        assert_eq!(
+            result.disclosed_values()["irma-demo.MijnOverheid.fullName.firstname"],
+            None
+        );
+        assert!(!result
+            .disclosed_values_present()
+            .contains_key("irma-demo.MijnOverheid.fullName.firstname"));
+    }
+
+    #[test]
+    fn test_require_attribute() {
+        let result = serde_json::from_str::<SessionResult>(include_str!(
+            "../tests/fixtures/disclosure_result.json"
+        ))
+        .unwrap();
+
+        assert_eq!(
+            result.require_attribute("irma-demo.MijnOverheid.ageLimits.over18"),
+            Ok("yes")
+        );
+        // The value borrows from the result only, not from the identifier
+        let value = {
+            let id = String::from("irma-demo.MijnOverheid.ageLimits.over18");
+            result.require_attribute(&id).unwrap()
+        };
+        assert_eq!(value, "yes");
+        assert_eq!(
+            result.require_attribute("irma-demo.MijnOverheid.ageLimits.over21"),
+            Err(ResultError::NotDisclosed(
+                "irma-demo.MijnOverheid.ageLimits.over21".into()
+            ))
+        );
+        assert_eq!(
+            result.require_attribute("pbdf.sidn-pbdf.email.email"),
+            Err(ResultError::NullValue("pbdf.sidn-pbdf.email.email".into()))
+        );
+
+        // Extra attributes were not requested, so they do not satisfy the requirement
+        let mut extra = result.clone();
+        extra.disclosed[0].remove(1);
+        assert_eq!(
+            extra.require_attribute("irma-demo.MijnOverheid.fullName.firstname"),
+            Err(ResultError::NotDisclosed(
+                "irma-demo.MijnOverheid.fullName.firstname".into()
+            ))
+        );
+
+        let invalid = SessionResult {
+            proof_status: Some(ProofStatus::Expired),
+            ..result.clone()
+        };
+        assert_eq!(
+            invalid.require_attribute("irma-demo.MijnOverheid.ageLimits.over18"),
+            Err(ResultError::SessionNotValid {
+                identifier: "irma-demo.MijnOverheid.ageLimits.over18".into(),
+                status: SessionStatus::Done,
+                proof_status: Some(ProofStatus::Expired),
+            })
+        );
+        let unfinished = SessionResult {
+            status: SessionStatus::Connected,
+            proof_status: None,
+            ..result
+        };
+        let err = unfinished
+            .require_attribute("irma-demo.MijnOverheid.ageLimits.over18")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot use attribute irma-demo.MijnOverheid.ageLimits.over18, session has status Connected and proof status None"
+        );
+        assert_eq!(
+            ResultError::NotDisclosed("irma-demo.MijnOverheid.ageLimits.over21".into()).to_string(),
+            "Attribute irma-demo.MijnOverheid.ageLimits.over21 was not disclosed"
+        );
+        assert_eq!(
+            ResultError::NullValue("pbdf.sidn-pbdf.email.email".into()).to_string(),
+            "Attribute pbdf.sidn-pbdf.email.email was disclosed without a value"
+        );
+    }
+
+    #[test]
+    fn test_signs_json() {
+        let result = serde_json::from_str::<SessionResult>(
+            r#"
+        {
+            "type" : "signing",
+            "status" : "DONE",
+            "token" : "ELMExi5iauWYHzbH7gwU",
+            "signature" : {
+                "@context" : "https://irma.app/ld/signature/v2",
+                "message" : "{\"amount\":12,\"party\":\"Alice\"}"
+            }
+        }
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            result.signed_message(),
+            Some(r#"{"amount":12,"party":"Alice"}"#)
+        );
+        assert!(result
+            .signs_json(&serde_json::json!({"party": "Alice", "amount": 12}))
+            .unwrap());
+        assert!(!result
+            .signs_json(&serde_json::json!({"party": "Bob", "amount": 12}))
+            .unwrap());
+
+        let unsigned = SessionResult {
+            signature: None,
+            ..result
+        };
+        assert_eq!(unsigned.signed_message(), None);
+        assert!(!unsigned.signs_json(&"anything").unwrap());
+    }
 }
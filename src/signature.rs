@@ -0,0 +1,300 @@
+//! Attribute-based signatures, the result of signing sessions
+
+use std::path::Path;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::value::RawValue;
+
+use crate::{Error, SessionResult, SessionToken};
+
+/// Attribute-based signature on a message, as found in the result of a signing session.
+///
+/// Verifying a signature with irmago requires its exact original JSON, so the signature is kept
+/// exactly as received from the irma server and serializes back to the very same JSON. The stable
+/// top-level fields are available through accessors; the cryptographic proofs themselves are only
+/// available as raw JSON.
+///
+/// Like [RawValue], a signature can only be deserialized directly from JSON text with serde_json,
+/// not from a parsed [serde_json::Value]. Signatures compare structurally as JSON values, so
+/// numbers compare by value rather than by their notation.
+#[derive(Debug, Clone)]
+pub struct IrmaSignature {
+    fields: SignatureFields,
+    raw: Box<RawValue>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SignatureFields {
+    #[serde(rename = "@context", default)]
+    ld_context: Option<String>,
+    message: String,
+    #[serde(default)]
+    signature: Option<Box<RawValue>>,
+    #[serde(default)]
+    indices: Vec<Vec<AttributeIndex>>,
+    #[serde(default)]
+    nonce: Option<String>,
+    #[serde(default)]
+    context: Option<String>,
+    #[serde(default)]
+    timestamp: Option<SignatureTimestamp>,
+}
+
+/// Location of a disclosed attribute within the proofs of a signature
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttributeIndex {
+    /// Index of the proof of the credential containing the attribute
+    pub cred: usize,
+    /// Index of the attribute within its credential
+    pub attr: usize,
+}
+
+/// Proof from a timestamp server that a signature was made at a particular time
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SignatureTimestamp {
+    /// Unix timestamp of when the signature was made
+    #[serde(rename = "Time")]
+    pub time: u64,
+    /// URL of the timestamp server
+    #[serde(rename = "ServerUrl")]
+    pub server_url: String,
+    /// Signature of the timestamp server on the time and the signature
+    #[serde(rename = "Sig")]
+    pub sig: TimestampSignature,
+}
+
+/// Signature of a timestamp server
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TimestampSignature {
+    /// Signature algorithm, e.g. `ed25519`
+    #[serde(rename = "Alg")]
+    pub alg: String,
+    /// Base64 encoded signature
+    #[serde(rename = "Data")]
+    pub data: String,
+    /// Base64 encoded public key of the timestamp server
+    #[serde(rename = "PublicKey")]
+    pub public_key: String,
+}
+
+impl IrmaSignature {
+    /// JSON-LD context of the signature, e.g. `https://irma.app/ld/signature/v2`, if present
+    pub fn ld_context(&self) -> Option<&str> {
+        self.fields.ld_context.as_deref()
+    }
+
+    /// The signed message
+    pub fn message(&self) -> &str {
+        &self.fields.message
+    }
+
+    /// The raw JSON of the proofs of the credentials of the disclosed attributes, if present
+    pub fn proofs(&self) -> Option<&RawValue> {
+        self.fields.signature.as_deref()
+    }
+
+    /// Location of the disclosed attributes within the proofs, grouped per disjunction of the
+    /// signature request
+    pub fn indices(&self) -> &[Vec<AttributeIndex>] {
+        &self.fields.indices
+    }
+
+    /// Base64 encoded nonce of the signature, if present
+    pub fn nonce(&self) -> Option<&str> {
+        self.fields.nonce.as_deref()
+    }
+
+    /// Base64 encoded context of the signature, if present
+    pub fn context(&self) -> Option<&str> {
+        self.fields.context.as_deref()
+    }
+
+    /// Timestamp of the signature, if present
+    pub fn timestamp(&self) -> Option<&SignatureTimestamp> {
+        self.fields.timestamp.as_ref()
+    }
+
+    /// The signature exactly as received from the irma server
+    pub fn raw(&self) -> &RawValue {
+        &self.raw
+    }
+
+    /// The signature as parsed JSON
+    pub fn to_value(&self) -> serde_json::Value {
+        // The raw value has been parsed successfully before, so it is valid JSON
+        serde_json::from_str(self.raw.get()).unwrap_or_default()
+    }
+}
+
+impl PartialEq for IrmaSignature {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw.get() == other.raw.get() || self.to_value() == other.to_value()
+    }
+}
+
+impl<'de> Deserialize<'de> for IrmaSignature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = Box::<RawValue>::deserialize(deserializer)?;
+        let fields = serde_json::from_str(raw.get()).map_err(de::Error::custom)?;
+        Ok(IrmaSignature { fields, raw })
+    }
+}
+
+impl Serialize for IrmaSignature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.raw.serialize(serializer)
+    }
+}
+
+/// A signature together with information on the session in which it was made, as stored in
+/// signature files by [write_to]. The stored signature is exactly the one received from the irma
+/// server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StoredSignature {
+    /// Token of the signing session
+    pub token: SessionToken,
+    /// Unix timestamp of when the signature was made, if it has a timestamp
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub timestamp: Option<u64>,
+    /// The signed message
+    pub message: String,
+    /// The signature itself
+    pub signature: IrmaSignature,
+}
+
+impl StoredSignature {
+    /// Take the signature from the result of a signing session, None when it contains no
+    /// signature
+    pub fn from_result(result: &SessionResult) -> Option<StoredSignature> {
+        let signature = result.signature.clone()?;
+        Some(StoredSignature {
+            token: result.token.clone(),
+            timestamp: signature.timestamp().map(|timestamp| timestamp.time),
+            message: signature.message().into(),
+            signature,
+        })
+    }
+}
+
+/// Write the signature to a file, conventionally with the extension `.irmasignature`. The file
+/// contains the signature exactly as received from the irma server, so that it can still be
+/// verified after reading it back with [read_from].
+pub fn write_to(path: impl AsRef<Path>, signature: &StoredSignature) -> Result<(), Error> {
+    let json = serde_json::to_vec_pretty(signature).map_err(Error::InvalidSignatureFile)?;
+    std::fs::write(path, json).map_err(Error::SignatureFile)
+}
+
+/// Read a signature file written by [write_to]. The message and timestamp stored next to the
+/// signature must match those of the signature itself, otherwise the file was tampered with and
+/// reading it fails with [Error::InvalidSignatureFile].
+pub fn read_from(path: impl AsRef<Path>) -> Result<StoredSignature, Error> {
+    let json = std::fs::read(path).map_err(Error::SignatureFile)?;
+    let stored: StoredSignature =
+        serde_json::from_slice(&json).map_err(Error::InvalidSignatureFile)?;
+    let mismatch = |field| {
+        Error::InvalidSignatureFile(de::Error::custom(format!(
+            "stored {} does not match the signature",
+            field
+        )))
+    };
+    if stored.message != stored.signature.message() {
+        return Err(mismatch("message"));
+    }
+    if stored.timestamp != stored.signature.timestamp().map(|timestamp| timestamp.time) {
+        return Err(mismatch("timestamp"));
+    }
+    Ok(stored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_from, write_to, IrmaSignature, StoredSignature};
+    use crate::{Error, SessionResult, SessionToken};
+
+    fn signature_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "irma-signature-{}-{}.irmasignature",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_signature_file_roundtrip() {
+        let original = include_str!("../tests/fixtures/signature.json").trim_end();
+        let result: SessionResult = serde_json::from_str(&format!(
+            r#"{{"token": "5bTpPRXctenYGGsZVe3x", "status": "DONE", "type": "signing", "proofStatus": "VALID", "signature": {}}}"#,
+            original
+        ))
+        .unwrap();
+        assert_eq!(result.signature_json().as_deref(), Some(original));
+
+        let stored = StoredSignature::from_result(&result).unwrap();
+        assert_eq!(stored.token, SessionToken("5bTpPRXctenYGGsZVe3x".into()));
+        assert_eq!(stored.timestamp, Some(1636103859));
+        assert_eq!(stored.message, "I agree to the terms");
+
+        let path = signature_path("roundtrip");
+        write_to(&path, &stored).unwrap();
+        // The signature is stored byte for byte, including its whitespace and number notation
+        assert!(std::fs::read_to_string(&path).unwrap().contains(original));
+
+        let read = read_from(&path).unwrap();
+        assert_eq!(read.signature.raw().get(), original);
+        assert_eq!(read, stored);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(read_from(&path), Err(Error::SignatureFile(_))));
+        std::fs::write(&path, "{}").unwrap();
+        assert!(matches!(
+            read_from(&path),
+            Err(Error::InvalidSignatureFile(_))
+        ));
+
+        // The message and timestamp next to the signature cannot be changed on their own
+        for tampered in [
+            StoredSignature {
+                message: "I do not agree".into(),
+                ..stored.clone()
+            },
+            StoredSignature {
+                timestamp: Some(1636103860),
+                ..stored.clone()
+            },
+            StoredSignature {
+                timestamp: None,
+                ..stored.clone()
+            },
+        ] {
+            write_to(&path, &tampered).unwrap();
+            assert!(matches!(
+                read_from(&path),
+                Err(Error::InvalidSignatureFile(_))
+            ));
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        let unsigned = SessionResult {
+            signature: None,
+            ..result
+        };
+        assert_eq!(unsigned.signature_json(), None);
+        assert!(StoredSignature::from_result(&unsigned).is_none());
+    }
+
+    #[test]
+    fn test_signature_without_context() {
+        let json = r#"{"message":"I agree to the terms","signature":[]}"#;
+        let signature: IrmaSignature = serde_json::from_str(json).unwrap();
+        assert_eq!(signature.ld_context(), None);
+        assert_eq!(signature.message(), "I agree to the terms");
+        assert_eq!(serde_json::to_string(&signature).unwrap(), json);
+
+        let result: SessionResult = serde_json::from_str(&format!(
+            r#"{{"token": "5bTpPRXctenYGGsZVe3x", "status": "DONE", "type": "signing", "signature": {}}}"#,
+            json
+        ))
+        .unwrap();
+        assert_eq!(result.signature, Some(signature));
+    }
+}
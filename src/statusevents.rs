@@ -0,0 +1,299 @@
+use std::{pin::Pin, time::Duration};
+
+use futures_util::{stream, Stream, StreamExt};
+use serde::Deserialize;
+use tokio::time::{sleep, timeout};
+
+use crate::{Error, IrmaClient, SessionStatus, SessionToken};
+
+type ByteStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>, reqwest::Error>> + Send>>;
+
+/// Incremental parser for server-sent events. Only the data field of events is retained,
+/// as that is the only field used by the irma server.
+#[derive(Debug, Default)]
+pub(crate) struct EventParser {
+    buffer: Vec<u8>,
+    data: Option<String>,
+}
+
+impl EventParser {
+    /// Add newly received bytes to the parser
+    pub(crate) fn feed(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Extract the data of the next complete event, if one has been received
+    pub(crate) fn next_event(&mut self) -> Option<String> {
+        while let Some(line) = self.next_line() {
+            if line.is_empty() {
+                if let Some(data) = self.data.take() {
+                    return Some(data);
+                }
+            } else if !line.starts_with(':') {
+                // Lines starting with a colon are comments, which servers use as keep-alive
+                let (field, value) = match line.find(':') {
+                    Some(index) => (&line[..index], &line[index + 1..]),
+                    None => (&line[..], ""),
+                };
+                let value = value.strip_prefix(' ').unwrap_or(value);
+                if field == "data" {
+                    match &mut self.data {
+                        Some(data) => {
+                            data.push('\n');
+                            data.push_str(value);
+                        }
+                        None => self.data = Some(value.into()),
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn next_line(&mut self) -> Option<String> {
+        let end = self.buffer.iter().position(|&b| b == b'\n' || b == b'\r')?;
+        let terminator_len = if self.buffer[end] == b'\r' {
+            match self.buffer.get(end + 1) {
+                Some(b'\n') => 2,
+                Some(_) => 1,
+                // The matching \n might still be underway
+                None => return None,
+            }
+        } else {
+            1
+        };
+        let line = String::from_utf8_lossy(&self.buffer[..end]).into_owned();
+        self.buffer.drain(..end + terminator_len);
+        Some(line)
+    }
+}
+
+/// Parse the payload of a status event. Depending on its version, the irma server either sends
+/// the status as a json string, or as an object with a status field.
+pub(crate) fn parse_status(data: &str) -> Result<SessionStatus, Error> {
+    #[derive(Deserialize)]
+    struct StatusEvent {
+        status: SessionStatus,
+    }
+
+    let data = data.trim();
+    let result = if data.starts_with('{') {
+        serde_json::from_str::<StatusEvent>(data).map(|event| event.status)
+    } else if data.starts_with('"') {
+        serde_json::from_str(data)
+    } else {
+        serde_json::from_value(serde_json::Value::String(data.into()))
+    };
+    result.map_err(Error::InvalidStatusEvent)
+}
+
+/// Options controlling how a status event stream deals with dropped connections
+#[derive(Debug, Clone)]
+pub struct StatusEventsOptions {
+    /// Delay before reconnecting after the first failure, doubled on every consecutive failure
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between reconnection attempts
+    pub max_backoff: Duration,
+    /// Number of consecutive failed connection attempts after which the stream ends with an error
+    pub max_failures: u32,
+    /// Time without receiving anything from the server (including keep-alives) after which the
+    /// connection is considered dead and proactively reestablished
+    pub max_idle: Option<Duration>,
+}
+
+impl Default for StatusEventsOptions {
+    fn default() -> Self {
+        StatusEventsOptions {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_failures: 5,
+            max_idle: None,
+        }
+    }
+}
+
+struct EventStream {
+    client: IrmaClient,
+    token: SessionToken,
+    options: StatusEventsOptions,
+    body: Option<ByteStream>,
+    parser: EventParser,
+    last: Option<SessionStatus>,
+    connected_before: bool,
+    received: bool,
+    failures: u32,
+    finished: bool,
+}
+
+impl EventStream {
+    async fn next_status(&mut self) -> Option<Result<SessionStatus, Error>> {
+        loop {
+            if let Some(data) = self.parser.next_event() {
+                match parse_status(&data) {
+                    Ok(status) if self.last.as_ref() == Some(&status) => continue,
+                    Ok(status) => {
+                        self.last = Some(status.clone());
+                        return Some(Ok(status));
+                    }
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+
+            let body = match &mut self.body {
+                Some(body) => body,
+                None => {
+                    match self.reconnect().await {
+                        Ok(Some(status)) if self.last.as_ref() != Some(&status) => {
+                            self.last = Some(status.clone());
+                            return Some(Ok(status));
+                        }
+                        Ok(_) => {}
+                        Err(e) => return Some(Err(e)),
+                    }
+                    continue;
+                }
+            };
+
+            let chunk = match self.options.max_idle {
+                Some(max_idle) => timeout(max_idle, body.next()).await.unwrap_or(None),
+                None => body.next().await,
+            };
+            match chunk {
+                Some(Ok(chunk)) => {
+                    self.received = true;
+                    self.failures = 0;
+                    self.parser.feed(&chunk);
+                }
+                // Connection dropped or idle, reconnect on the next iteration
+                _ => {
+                    if !self.received {
+                        self.failures += 1;
+                    }
+                    self.body = None;
+                }
+            }
+        }
+    }
+
+    // (Re)establish the connection to the server. After a reconnect, the current status is
+    // also fetched, as the server might have sent status changes when we were not connected.
+    async fn reconnect(&mut self) -> Result<Option<SessionStatus>, Error> {
+        self.parser = EventParser::default();
+        self.received = false;
+        loop {
+            if self.failures >= self.options.max_failures {
+                return Err(Error::StatusEventsDisconnected);
+            }
+            if self.failures > 0 {
+                let backoff = self
+                    .options
+                    .initial_backoff
+                    .saturating_mul(2u32.saturating_pow(self.failures - 1))
+                    .min(self.options.max_backoff);
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    failures = self.failures,
+                    backoff_ms = backoff.as_millis() as u64,
+                    "reconnecting to irma status events"
+                );
+                sleep(backoff).await;
+            }
+
+            match self.connect().await {
+                Ok(result) => return Ok(result),
+                Err(e) => {
+                    self.failures += 1;
+                    if self.failures >= self.options.max_failures {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn connect(&mut self) -> Result<Option<SessionStatus>, Error> {
+        let response = self
+            .client
+            .statusevents(&self.token)
+            .await?
+            .error_for_status()?;
+        let status = if self.connected_before {
+            Some(self.client.status(&self.token).await?)
+        } else {
+            None
+        };
+        self.connected_before = true;
+        self.body = Some(Box::pin(
+            response
+                .bytes_stream()
+                .map(|chunk| chunk.map(|bytes| bytes.to_vec())),
+        ));
+        Ok(status)
+    }
+}
+
+/// Stream the statuses send by the server for the given session, until either a terminal status
+/// is received, an error occurs, or reconnecting to the server failed too often.
+pub(crate) fn status_events(
+    client: IrmaClient,
+    token: SessionToken,
+    options: StatusEventsOptions,
+) -> impl Stream<Item = Result<SessionStatus, Error>> {
+    let state = EventStream {
+        client,
+        token,
+        options,
+        body: None,
+        parser: EventParser::default(),
+        last: None,
+        connected_before: false,
+        received: false,
+        failures: 0,
+        finished: false,
+    };
+    stream::unfold(state, |mut state| async move {
+        if state.finished {
+            return None;
+        }
+        let item = state.next_status().await?;
+        state.finished = match &item {
+            Ok(status) => status.is_terminal(),
+            Err(_) => true,
+        };
+        Some((item, state))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_status, EventParser};
+    use crate::SessionStatus;
+
+    #[test]
+    fn test_event_parser() {
+        let mut parser = EventParser::default();
+        parser.feed(b": keep-alive\n\ndata: \"INITIALIZED\"\n\nda");
+        assert_eq!(parser.next_event(), Some("\"INITIALIZED\"".into()));
+        assert_eq!(parser.next_event(), None);
+
+        parser.feed(b"ta: first\r\ndata:second\r");
+        assert_eq!(parser.next_event(), None);
+        parser.feed(b"\n\r\n");
+        assert_eq!(parser.next_event(), Some("first\nsecond".into()));
+
+        parser.feed(b"event: status\nid: 1\ndata: \"DONE\"\n\n: ping\n\n");
+        assert_eq!(parser.next_event(), Some("\"DONE\"".into()));
+        assert_eq!(parser.next_event(), None);
+    }
+
+    #[test]
+    fn test_parse_status() {
+        assert_eq!(parse_status("\"DONE\"").unwrap(), SessionStatus::Done);
+        assert_eq!(parse_status("CONNECTED").unwrap(), SessionStatus::Connected);
+        assert_eq!(
+            parse_status("{\"status\":\n\"PAIRING\"}").unwrap(),
+            SessionStatus::Pairing
+        );
+        assert!(parse_status("\"UNKNOWN\"").is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use reqwest::header::HeaderValue;
+
+use crate::{irmaclient::TokenSecret, Error};
+
+/// When the requestor token is re-read from its file, see
+/// [crate::IrmaClientBuilder::token_authentication_from_file]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReloadPolicy {
+    /// Only read the file when building the client
+    Never,
+    /// Read the file for every request to the irma server
+    EveryRequest,
+    /// Read the file when the token was last read longer than the given duration ago
+    Every(Duration),
+}
+
+/// Requestor token read from a file, for deployments in which the token is rotated by replacing
+/// the file
+pub(crate) struct TokenFile {
+    path: PathBuf,
+    policy: ReloadPolicy,
+    state: Mutex<Option<LoadedToken>>,
+}
+
+struct LoadedToken {
+    secret: TokenSecret,
+    loaded: Instant,
+}
+
+impl TokenFile {
+    pub(crate) fn new(path: PathBuf, policy: ReloadPolicy) -> TokenFile {
+        TokenFile {
+            path,
+            policy,
+            state: Mutex::new(None),
+        }
+    }
+
+    /// Read the token for the first time, failing when the file cannot be read. This blocks, as
+    /// it is only done once while building the client.
+    pub(crate) fn load(&self) -> Result<(), Error> {
+        self.store(parse(std::fs::read_to_string(&self.path))?);
+        Ok(())
+    }
+
+    /// The (sensitive) value of the Authorization header, re-reading the token when due according
+    /// to the policy. After a failure to re-read the file, the last successfully read token is
+    /// used.
+    pub(crate) async fn header_value(&self) -> HeaderValue {
+        if self.due() {
+            self.reload(parse(tokio::fs::read_to_string(&self.path).await));
+        }
+        self.current()
+    }
+
+    /// As [TokenFile::header_value], reading the file synchronously for the blocking client
+    #[cfg(feature = "blocking")]
+    pub(crate) fn header_value_blocking(&self) -> HeaderValue {
+        if self.due() {
+            self.reload(parse(std::fs::read_to_string(&self.path)));
+        }
+        self.current()
+    }
+
+    // The lock is only held for inspecting and replacing the token, never while reading the
+    // file, so concurrent requests might occasionally both re-read it
+    fn due(&self) -> bool {
+        match (&*self.state.lock().unwrap(), self.policy) {
+            (None, _) | (Some(_), ReloadPolicy::EveryRequest) => true,
+            (Some(_), ReloadPolicy::Never) => false,
+            (Some(loaded), ReloadPolicy::Every(interval)) => loaded.loaded.elapsed() >= interval,
+        }
+    }
+
+    fn reload(&self, secret: Result<TokenSecret, Error>) {
+        match secret {
+            Ok(secret) => self.store(secret),
+            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+            Err(e) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    path = %self.path.display(),
+                    error = %e,
+                    "failed to reload requestor token, keeping the last one"
+                );
+            }
+        }
+    }
+
+    fn store(&self, secret: TokenSecret) {
+        *self.state.lock().unwrap() = Some(LoadedToken {
+            secret,
+            loaded: Instant::now(),
+        });
+    }
+
+    fn current(&self) -> HeaderValue {
+        self.state
+            .lock()
+            .unwrap()
+            .as_ref()
+            // Tokens are only stored after checking that they form a valid header value
+            .and_then(|loaded| loaded.secret.header_value().ok())
+            .unwrap_or_else(|| HeaderValue::from_static(""))
+    }
+}
+
+// Surrounding whitespace, such as a trailing newline, is not part of the token. A token that
+// cannot be sent in a header is rejected like a file that cannot be read. The contents of the file
+// are wiped once the token has been copied out of them.
+fn parse(token: std::io::Result<String>) -> Result<TokenSecret, Error> {
+    let token = token.map_err(Error::InvalidTokenFile)?;
+    #[cfg(feature = "zeroize")]
+    let token = zeroize::Zeroizing::new(token);
+    let secret = TokenSecret::new(token.trim().into());
+    secret.header_value()?;
+    Ok(secret)
+}
+
+// The path is not secret, but the token read from it is
+impl Debug for TokenFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenFile")
+            .field("path", &self.path)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{ReloadPolicy, TokenFile};
+    use crate::Error;
+
+    fn token_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("irma-token-{}-{}", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn test_reload_policy() {
+        let path = token_path("policy");
+        std::fs::write(&path, "first\n").unwrap();
+        let never = TokenFile::new(path.clone(), ReloadPolicy::Never);
+        let every = TokenFile::new(path.clone(), ReloadPolicy::EveryRequest);
+        let interval = TokenFile::new(path.clone(), ReloadPolicy::Every(Duration::from_secs(3600)));
+        for file in [&never, &every, &interval] {
+            file.load().unwrap();
+            assert_eq!(file.header_value().await, "first");
+        }
+
+        std::fs::write(&path, "second").unwrap();
+        assert_eq!(never.header_value().await, "first");
+        assert_eq!(every.header_value().await, "second");
+        assert_eq!(interval.header_value().await, "first");
+
+        // The last token is kept when the file disappears
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(every.header_value().await, "second");
+        assert!(matches!(every.load(), Err(Error::InvalidTokenFile(_))));
+    }
+}
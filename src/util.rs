@@ -2,18 +2,115 @@ use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::{self, Display};
+use std::future::Future;
 use std::hash::Hash;
 use std::marker::PhantomData;
+use std::pin::Pin;
 use std::str::FromStr;
 
+/// Boxed future as returned by the methods of the traits of this crate, such as
+/// [RequestSigner](crate::RequestSigner)
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Boxed future as returned by the methods of the traits of this crate, such as
+/// [RequestSigner](crate::RequestSigner). Browser futures are not `Send`, so neither is this.
+#[cfg(target_arch = "wasm32")]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
 /// String that can be displayed in multiple languages
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct TranslatedString {
     pub en: String,
     pub nl: String,
 }
 
+impl TranslatedString {
+    /// Create a string with the given english and dutch translations
+    pub fn new(en: impl Into<String>, nl: impl Into<String>) -> TranslatedString {
+        TranslatedString {
+            en: en.into(),
+            nl: nl.into(),
+        }
+    }
+
+    /// Create a string that is the same in all languages
+    pub fn single(value: impl Into<String>) -> TranslatedString {
+        let value = value.into();
+        TranslatedString {
+            en: value.clone(),
+            nl: value,
+        }
+    }
+}
+
+/// Same in all languages, see [TranslatedString::single]
+impl From<&str> for TranslatedString {
+    fn from(value: &str) -> Self {
+        TranslatedString::single(value)
+    }
+}
+
+/// Same in all languages, see [TranslatedString::single]
+impl From<String> for TranslatedString {
+    fn from(value: String) -> Self {
+        TranslatedString::single(value)
+    }
+}
+
+/// Displays the english translation, or the dutch one when there is no english translation
+impl Display for TranslatedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.en.is_empty() {
+            f.write_str(&self.nl)
+        } else {
+            f.write_str(&self.en)
+        }
+    }
+}
+
+/// Serialize a value to JSON in canonical form, suitable as message of a signature request.
+///
+/// The canonical form contains no whitespace outside of strings, and the keys of every object are
+/// sorted by their UTF-8 bytes. Strings and numbers are written as by [serde_json], so equal values
+/// always produce the same string, regardless of field order or platform.
+pub fn canonical_json<T: Serialize + ?Sized>(value: &T) -> Result<String, crate::Error> {
+    let value = serde_json::to_value(value).map_err(crate::Error::InvalidMessage)?;
+    let mut out = String::new();
+    write_canonical(&value, &mut out);
+    Ok(out)
+}
+
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
 // Utility for deserializing hashmaps with integers keys properly when coming from json
 // Original deserializer suggested by Dtolnay in https://github.com/serde-rs/json/issues/560
 // Can be removed once the underlying issue in serde/serde_json is fixed.
@@ -98,3 +195,13 @@ where
         v: PhantomData,
     })
 }
+
+// Deserializer for options that distinguish an explicit null from an absent field: combined with
+// `#[serde(default)]`, an absent field becomes None and null becomes Some(None)
+pub fn de_explicit<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
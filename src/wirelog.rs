@@ -0,0 +1,138 @@
+use reqwest::{header::HeaderMap, Request, Response};
+
+// Target of the wire log events, so they can be enabled separately from the other events
+const TARGET: &str = "irma::wire";
+const REDACTED: &str = "[REDACTED]";
+
+/// Debug logging of the requests sent to and responses received from the irma server, with
+/// credentials and JWTs redacted
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WireLog {
+    // Maximum number of bytes of a body that is logged
+    pub(crate) max_body_len: usize,
+}
+
+impl WireLog {
+    // The secret header is the header carrying the requestor token, when that is not the
+    // Authorization header
+    pub(crate) fn log_request(&self, request: &Request, secret_header: Option<&str>) {
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|body| self.format_body(body))
+            .unwrap_or_default();
+        tracing::debug!(
+            target: TARGET,
+            method = %request.method(),
+            url = %request.url(),
+            headers = %format_headers(request.headers(), secret_header),
+            body = %body,
+            "irma request"
+        );
+    }
+
+    // Reading the body for logging consumes the response, so a new response is constructed from
+    // the received parts
+    pub(crate) async fn log_response(
+        &self,
+        response: Response,
+    ) -> Result<Response, reqwest::Error> {
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        tracing::debug!(
+            target: TARGET,
+            status = status.as_u16(),
+            headers = %format_headers(&headers, None),
+            body = %self.format_body(&body),
+            "irma response"
+        );
+
+        let mut rebuilt = http::Response::new(body);
+        *rebuilt.status_mut() = status;
+        *rebuilt.version_mut() = version;
+        *rebuilt.headers_mut() = headers;
+        Ok(Response::from(rebuilt))
+    }
+
+    fn format_body(&self, body: &[u8]) -> String {
+        let mut body = redact_jwts(&String::from_utf8_lossy(body));
+        if body.len() > self.max_body_len {
+            let total = body.len();
+            let mut end = self.max_body_len;
+            while !body.is_char_boundary(end) {
+                end -= 1;
+            }
+            body.truncate(end);
+            body.push_str(&format!("... ({} bytes total)", total));
+        }
+        body
+    }
+}
+
+fn format_headers(headers: &HeaderMap, secret_header: Option<&str>) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let secret = name == reqwest::header::AUTHORIZATION
+                || name == reqwest::header::PROXY_AUTHORIZATION
+                || secret_header.is_some_and(|secret| name.as_str().eq_ignore_ascii_case(secret));
+            if secret {
+                format!("{}: {}", name, REDACTED)
+            } else {
+                format!("{}: {}", name, String::from_utf8_lossy(value.as_bytes()))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Replace everything looking like a compact JWT (base64url segments separated by dots, starting
+// with the encoding of `{"`) by a placeholder
+fn redact_jwts(text: &str) -> String {
+    let is_jwt_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.';
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("eyJ") {
+        let candidate = &rest[start..];
+        let len = candidate
+            .find(|c: char| !is_jwt_char(c))
+            .unwrap_or(candidate.len());
+        result.push_str(&rest[..start]);
+        if candidate[..len].contains('.') {
+            result.push_str(REDACTED);
+        } else {
+            result.push_str(&candidate[..len]);
+        }
+        rest = &candidate[len..];
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{redact_jwts, WireLog};
+
+    #[test]
+    fn test_redact_jwts() {
+        assert_eq!(
+            redact_jwts(r#"{"jwt":"eyJhbGciOiJub25lIn0.eyJzdWIiOiJ4In0.","n":"eyJnot"}"#),
+            r#"{"jwt":"[REDACTED]","n":"eyJnot"}"#
+        );
+        assert_eq!(redact_jwts("eyJa.b-_c.d"), "[REDACTED]");
+        assert_eq!(redact_jwts("no tokens"), "no tokens");
+    }
+
+    #[test]
+    fn test_truncate_body() {
+        let wire = WireLog { max_body_len: 4 };
+        assert_eq!(wire.format_body(b"abcdefgh"), "abcd... (8 bytes total)");
+        assert_eq!(wire.format_body(b"abc"), "abc");
+        assert_eq!(
+            wire.format_body("abcé".as_bytes()),
+            "abc... (5 bytes total)"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use irma::{
+    AttributeRequest, AttributeStatus, CredentialBuilder, DisclosedAttribute,
+    DisclosureRequestBuilder, ExtendedIrmaRequest, IrmaRequest, IssuanceRequestBuilder, Qr,
+    SessionData, SessionResult, SessionStatus, SessionToken, SessionType, SignatureRequestBuilder,
+    TranslatedString,
+};
+
+#[test]
+fn test_request_from_config_equals_built_request() {
+    let loaded: IrmaRequest = serde_json::from_str(
+        r#"{
+            "@context": "https://irma.app/ld/request/disclosure/v2",
+            "disclose": [[["irma-demo.MijnOverheid.ageLimits.over18"]]],
+            "labels": {"0": {"en": "Age", "nl": "Leeftijd"}}
+        }"#,
+    )
+    .unwrap();
+    let built = DisclosureRequestBuilder::new()
+        .add_discon_with_label(
+            vec![vec!["irma-demo.MijnOverheid.ageLimits.over18".into()]],
+            TranslatedString::new("Age", "Leeftijd"),
+        )
+        .build();
+    assert_eq!(loaded, built);
+
+    let other = DisclosureRequestBuilder::new()
+        .add_attribute("irma-demo.MijnOverheid.ageLimits.over21")
+        .build();
+    assert_ne!(loaded, other);
+
+    let signature = SignatureRequestBuilder::new("message".into())
+        .add_attribute("irma-demo.MijnOverheid.ageLimits.over18")
+        .build();
+    assert_ne!(
+        signature,
+        SignatureRequestBuilder::new("other message".into())
+            .add_attribute("irma-demo.MijnOverheid.ageLimits.over18")
+            .build()
+    );
+
+    let issuance = IssuanceRequestBuilder::new()
+        .add_credential(
+            CredentialBuilder::new("irma-demo.MijnOverheid.root".into())
+                .attribute("BSN".into(), "12345".into())
+                .build(),
+        )
+        .build();
+    assert_eq!(issuance, issuance.clone());
+    assert_ne!(issuance, built);
+
+    let extended = ExtendedIrmaRequest {
+        validity: None,
+        timeout: Some(Some(60)),
+        callback_url: None,
+        next_session: None,
+        host: None,
+        request: built.clone(),
+    };
+    assert_eq!(extended, extended.clone());
+    assert_ne!(
+        extended,
+        ExtendedIrmaRequest {
+            timeout: None,
+            ..extended.clone()
+        }
+    );
+}
+
+#[test]
+fn test_attribute_request_equality() {
+    assert_eq!(
+        AttributeRequest::from(("irma-demo.a.b.c", "value")),
+        AttributeRequest::with_value("irma-demo.a.b.c", "value")
+    );
+    assert_ne!(
+        AttributeRequest::Simple("irma-demo.a.b.c".into()),
+        AttributeRequest::non_null("irma-demo.a.b.c")
+    );
+}
+
+#[test]
+fn test_session_result_equality() {
+    let result = SessionResult {
+        token: SessionToken("ELMExi5iauWYHzbH7gwU".into()),
+        sessiontype: SessionType::Signing,
+        status: SessionStatus::Done,
+        proof_status: None,
+        disclosed: vec![vec![DisclosedAttribute {
+            raw_value: Some("yes".into()),
+            value: Some("yes".into()),
+            identifier: "irma-demo.MijnOverheid.ageLimits.over18".into(),
+            status: AttributeStatus::Present,
+            issuance_time: None,
+            not_revoked: None,
+            not_revoked_before: None,
+        }]],
+        signature: Some(
+            serde_json::from_str(
+                r#"{"@context": "https://irma.app/ld/signature/v2", "message": "message", "nonce": "AQ==", "signature": [{"c": 1.0}]}"#,
+            )
+            .unwrap(),
+        ),
+        next_session: None,
+    };
+
+    // Signatures compare as JSON values, independent of how numbers were written
+    let parsed: SessionResult = serde_json::from_str(
+        r#"{
+            "token": "ELMExi5iauWYHzbH7gwU",
+            "type": "signing",
+            "status": "DONE",
+            "disclosed": [[{
+                "rawvalue": "yes",
+                "value": {"en": "yes", "nl": "yes"},
+                "id": "irma-demo.MijnOverheid.ageLimits.over18",
+                "status": "PRESENT"
+            }]],
+            "signature": {"signature": [{"c": 1.00}], "nonce": "AQ==", "@context": "https://irma.app/ld/signature/v2", "message": "message"}
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(parsed, result);
+
+    assert_ne!(
+        result,
+        SessionResult {
+            signature: None,
+            ..result.clone()
+        }
+    );
+}
+
+#[test]
+fn test_session_data_equality() {
+    let data = SessionData {
+        session_ptr: Qr {
+            u: "https://example.com/irma/session/abc".into(),
+            irmaqr: SessionType::Disclosing,
+        },
+        token: SessionToken("abc".into()),
+    };
+    assert_eq!(data, data.clone());
+    assert_ne!(
+        data,
+        SessionData {
+            token: SessionToken("def".into()),
+            ..data.clone()
+        }
+    );
+}
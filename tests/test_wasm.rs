@@ -0,0 +1,56 @@
+// Run with: wasm-pack test --node -- --test test_wasm
+#![cfg(target_arch = "wasm32")]
+
+use irma::{
+    AttributeRequest, BoxFuture, DisclosureRequestBuilder, IrmaClient, IrmaClientBuilder,
+    RequestSigner, SignError, Signature,
+};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_wasm_serialization() {
+    let request = DisclosureRequestBuilder::new()
+        .add_discon(vec![vec![AttributeRequest::Simple("a.b.c.d".into())]])
+        .build();
+    assert_eq!(
+        "{\"@context\":\"https://irma.app/ld/request/disclosure/v2\",\"disclose\":[[[\"a.b.c.d\"]]]}",
+        serde_json::to_string(&request).unwrap()
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_wasm_client() {
+    IrmaClient::new("https://irma.example.com/").unwrap();
+    IrmaClientBuilder::new("https://irma.example.com/")
+        .unwrap()
+        .token_authentication("token".into())
+        .build()
+        .unwrap();
+}
+
+// Signers backed by browser APIs return futures that are not Send
+struct BrowserSigner;
+
+impl RequestSigner for BrowserSigner {
+    fn alg(&self) -> &str {
+        "HS256"
+    }
+
+    fn issuer(&self) -> &str {
+        "testsp"
+    }
+
+    fn sign<'a>(&'a self, _: &'a [u8]) -> BoxFuture<'a, Result<Signature, SignError>> {
+        let not_send = std::rc::Rc::new(vec![0; 32]);
+        Box::pin(async move { Ok(Signature(not_send.to_vec())) })
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_wasm_signer() {
+    IrmaClientBuilder::new("https://irma.example.com/")
+        .unwrap()
+        .custom_signer(std::sync::Arc::new(BrowserSigner))
+        .build()
+        .unwrap();
+}